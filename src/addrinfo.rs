@@ -3,7 +3,7 @@ use std::ffi::{CStr, CString};
 use std::io;
 use std::iter::FusedIterator;
 use std::mem;
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::os::raw::c_char;
 use std::ptr;
 
@@ -21,15 +21,17 @@ use windows_sys::Win32::Networking::WinSock::{
     freeaddrinfo as c_freeaddrinfo, getaddrinfo as c_getaddrinfo, ADDRINFOA as c_addrinfo,
 };
 
-use crate::err::LookupError;
+use crate::err::{last_errno, LookupError, LookupErrorKind};
+use crate::types::{AddrFamily, AddrInfoFlags, Protocol, SockType};
 
 /// A struct used as the hints argument to getaddrinfo.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct AddrInfoHints {
     /// Optional bitmask arguments. Bitwise OR bitflags to change the
     /// behaviour of getaddrinfo. 0 for none. `ai_flags` in libc.
     ///
-    /// Values are defined by the libc on your system.
+    /// Values are defined by the libc on your system, or can be converted
+    /// from `AddrInfoFlags`.
     pub flags: i32,
     /// Address family for this socket. 0 for none. `ai_family` in libc.
     ///
@@ -46,17 +48,33 @@ pub struct AddrInfoHints {
 }
 
 impl AddrInfoHints {
+    /// Start building an AddrInfoHints using built-in types.
+    ///
+    /// ```rust
+    ///   use dns_lookup::{AddrFamily, AddrInfoFlags, AddrInfoHints, SockType};
+    ///
+    ///   let hints = AddrInfoHints::builder()
+    ///     .family(AddrFamily::Inet6)
+    ///     .socktype(SockType::Stream)
+    ///     .flags(AddrInfoFlags::CANONNAME)
+    ///     .build()
+    ///     .unwrap();
+    ///   assert_eq!(hints.socktype, SockType::Stream);
+    /// ```
+    pub fn builder() -> AddrInfoHintsBuilder {
+        AddrInfoHintsBuilder::default()
+    }
+
     /// Create a new AddrInfoHints using built-in types.
     ///
     /// Included Enums only provide common values, for anything else
     /// create this struct directly using appropriate values from the
     /// libc crate.
-    #[allow(dead_code)]
     fn new(
         flags: Option<i32>,
-        address: Option<crate::AddrFamily>,
-        socktype: Option<crate::SockType>,
-        protocol: Option<crate::Protocol>,
+        address: Option<AddrFamily>,
+        socktype: Option<SockType>,
+        protocol: Option<Protocol>,
     ) -> AddrInfoHints {
         AddrInfoHints {
             flags: flags.unwrap_or(0),
@@ -92,6 +110,85 @@ impl Default for AddrInfoHints {
     }
 }
 
+/// Builder for `AddrInfoHints`, created by `AddrInfoHints::builder`.
+///
+/// Unset fields are left as 0, letting getaddrinfo choose any value.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AddrInfoHintsBuilder {
+    flags: AddrInfoFlags,
+    family: Option<AddrFamily>,
+    socktype: Option<SockType>,
+    protocol: Option<Protocol>,
+}
+
+impl AddrInfoHintsBuilder {
+    /// Set the flags, replacing any previously set.
+    pub fn flags(mut self, flags: AddrInfoFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Set the address family.
+    pub fn family(mut self, family: AddrFamily) -> Self {
+        self.family = Some(family);
+        self
+    }
+
+    /// Set the socket type.
+    pub fn socktype(mut self, socktype: SockType) -> Self {
+        self.socktype = Some(socktype);
+        self
+    }
+
+    /// Set the protocol.
+    pub fn protocol(mut self, protocol: Protocol) -> Self {
+        self.protocol = Some(protocol);
+        self
+    }
+
+    /// Create the hints, checking the socket type and protocol can be used
+    /// together. Any protocol can be used with `SockType::Raw`, but ICMP
+    /// can't be used with any other socket type.
+    pub fn build(self) -> Result<AddrInfoHints, LookupError> {
+        if let (Some(socktype), Some(protocol)) = (self.socktype, self.protocol) {
+            let supported = match protocol {
+                Protocol::TCP => socktype == SockType::Stream,
+                Protocol::UDP => socktype == SockType::DGram,
+                // getaddrinfo has no mapping for ICMP datagram sockets.
+                Protocol::ICMP | Protocol::ICMPv6 => false,
+                #[cfg(any(
+                    windows,
+                    target_os = "linux",
+                    target_os = "android",
+                    target_os = "freebsd",
+                    target_os = "netbsd",
+                    target_vendor = "apple"
+                ))]
+                Protocol::SCTP => socktype == SockType::Stream || socktype == SockType::SeqPacket,
+                #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+                Protocol::UDPLite => socktype == SockType::DGram,
+                #[cfg(any(target_os = "linux", target_os = "android"))]
+                Protocol::MPTCP => socktype == SockType::Stream,
+            };
+            #[cfg(not(target_os = "redox"))]
+            let supported = supported || socktype == SockType::Raw;
+            if !supported {
+                return Err(LookupError::invalid(
+                    LookupErrorKind::Socktype,
+                    format!("Protocol {protocol:?} is not supported by socket type {socktype:?}"),
+                ));
+            }
+        }
+
+        Ok(AddrInfoHints::new(
+            Some(self.flags.into()),
+            self.family,
+            self.socktype,
+            self.protocol,
+        ))
+    }
+}
+
 /// Struct that stores socket information, as returned by getaddrinfo.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct AddrInfo {
@@ -117,8 +214,84 @@ pub struct AddrInfo {
 }
 
 impl AddrInfo {
+    /// The address family of this socket, or an error if it isn't one of
+    /// the values in `AddrFamily`.
+    pub fn family(&self) -> io::Result<AddrFamily> {
+        AddrFamily::try_from(self.address)
+    }
+
+    /// The type of this socket, or an error if it isn't one of the values
+    /// in `SockType`.
+    pub fn sock_type(&self) -> io::Result<SockType> {
+        SockType::try_from(self.socktype)
+    }
+
+    /// The protocol of this socket, or an error if it isn't one of the
+    /// values in `Protocol`.
+    pub fn protocol(&self) -> io::Result<Protocol> {
+        Protocol::try_from(self.protocol)
+    }
+}
+
+impl TryFrom<RawAddrInfo> for AddrInfo {
+    type Error = io::Error;
+
+    /// Convert socket information for an IPv4 or IPv6 address, returning
+    /// an error for other address families.
+    fn try_from(raw: RawAddrInfo) -> io::Result<AddrInfo> {
+        let sockaddr = raw.sockaddr.as_socket().ok_or_else(|| {
+            io::Error::other(format!(
+                "Found unknown address family: {}",
+                raw.sockaddr.family()
+            ))
+        })?;
+        Ok(AddrInfo {
+            flags: raw.flags,
+            address: raw.address,
+            socktype: raw.socktype,
+            protocol: raw.protocol,
+            sockaddr,
+            canonname: raw
+                .canonname
+                .map(String::from_utf8)
+                .transpose()
+                .map_err(|_| io::Error::other("Canonical name UTF8 parsing failed"))?,
+        })
+    }
+}
+
+/// Socket information for any address family, as returned by getaddrinfo.
+/// Created by `AddrInfoIter::raw`.
+///
+/// Unlike `AddrInfo`, this can hold address families other than IPv4 and
+/// IPv6, such as `AF_UNIX`, which some NSS modules and libcs return.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RawAddrInfo {
+    /// Optional bitmask arguments, usually set to zero. `ai_flags` in libc.
+    pub flags: i32,
+    /// Address family for this socket. `ai_family` in libc.
+    ///
+    /// Values are defined by the libc on your system.
+    pub address: i32,
+    /// Type of this socket. `ai_socktype` in libc.
+    ///
+    /// Values are defined by the libc on your system.
+    pub socktype: i32,
+    /// Protcol family for this socket. `ai_protocol` in libc.
+    ///
+    /// Values are defined by the libc on your system.
+    pub protocol: i32,
+    /// Socket address for this socket, in any address family. Combination
+    /// of `ai_addrlen` and `ai_addr` in libc.
+    pub sockaddr: SockAddr,
+    /// If requested, this is the canonical name for this socket/host, as
+    /// raw bytes that may not be valid UTF8. `ai_canonname` in libc.
+    pub canonname: Option<Vec<u8>>,
+}
+
+impl RawAddrInfo {
     /// Copy the informataion from the given addrinfo pointer, and
-    /// create a new AddrInfo struct with that information.
+    /// create a new RawAddrInfo struct with that information.
     ///
     /// Used for interfacing with getaddrinfo.
     unsafe fn from_ptr(a: *mut c_addrinfo) -> io::Result<Self> {
@@ -138,34 +311,43 @@ impl AddrInfo {
                 );
                 Ok(())
             })?;
-            let sock = sockaddr.as_socket().ok_or_else(|| {
-                io::Error::other(format!(
-                    "Found unknown address family: {}",
-                    sockaddr.family()
-                ))
-            })?;
-            Ok(AddrInfo {
+            Ok(RawAddrInfo {
                 flags: 0,
                 address: addrinfo.ai_family,
                 socktype: addrinfo.ai_socktype,
                 protocol: addrinfo.ai_protocol,
-                sockaddr: sock,
+                sockaddr,
                 canonname: addrinfo.ai_canonname.as_ref().map(|s| {
                     CStr::from_ptr(s as *const libc_c_char as *const c_char)
-                        .to_str()
-                        .unwrap()
-                        .to_owned()
+                        .to_bytes()
+                        .to_vec()
                 }),
             })
         }
     }
 }
 
+impl From<AddrInfo> for RawAddrInfo {
+    fn from(addr: AddrInfo) -> RawAddrInfo {
+        RawAddrInfo {
+            flags: addr.flags,
+            address: addr.address,
+            socktype: addr.socktype,
+            protocol: addr.protocol,
+            sockaddr: addr.sockaddr.into(),
+            canonname: addr.canonname.map(String::into_bytes),
+        }
+    }
+}
+
 /// An iterator of `AddrInfo` structs, wrapping a linked-list
 /// returned by getaddrinfo.
 ///
 /// It's recommended to use `.collect<io::Result<..>>()` on this
 /// to collapse possible errors.
+///
+/// Results with address families other than IPv4 and IPv6 are skipped. Use
+/// `raw` to iterate over every result.
 pub struct AddrInfoIter {
     orig: *mut c_addrinfo,
     cur: *mut c_addrinfo,
@@ -175,27 +357,116 @@ impl Iterator for AddrInfoIter {
     type Item = io::Result<AddrInfo>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        unsafe {
-            if self.cur.is_null() {
-                return None;
+        loop {
+            let raw = match unsafe { RawAddrInfo::from_ptr(self.next_ptr()?) } {
+                Ok(raw) => raw,
+                Err(e) => return Some(Err(e)),
+            };
+            if raw.sockaddr.is_ipv4() || raw.sockaddr.is_ipv6() {
+                return Some(AddrInfo::try_from(raw));
             }
-            let ret = AddrInfo::from_ptr(self.cur);
-            #[allow(clippy::unnecessary_cast)]
-            {
-                self.cur = (*self.cur).ai_next as *mut c_addrinfo;
+        }
+    }
+}
+
+impl AddrInfoIter {
+    /// Iterate over every result, including address families other than
+    /// IPv4 and IPv6.
+    pub fn raw(self) -> RawAddrInfoIter {
+        RawAddrInfoIter(self)
+    }
+
+    // Step to the next entry in the linked list.
+    fn next_ptr(&mut self) -> Option<*mut c_addrinfo> {
+        if self.cur.is_null() {
+            return None;
+        }
+        let ptr = self.cur;
+        #[allow(clippy::unnecessary_cast)]
+        unsafe {
+            self.cur = (*self.cur).ai_next as *mut c_addrinfo;
+        }
+        Some(ptr)
+    }
+
+    // Collect the IP addresses from these results.
+    pub(crate) fn collect_ips(self) -> io::Result<Vec<IpAddr>> {
+        self.map(|r| r.map(|a| a.sockaddr.ip())).collect()
+    }
+
+    /// Collapse results into one entry per socket address.
+    ///
+    /// Without a socktype in the hints, libc returns each address once per
+    /// supported socktype. This groups them, listing every
+    /// `(socktype, protocol)` pair for the address, in the order they were
+    /// first returned.
+    pub fn group_by_address(self) -> io::Result<Vec<GroupedAddrInfo>> {
+        let mut groups: Vec<GroupedAddrInfo> = Vec::new();
+        let mut canonname = None;
+        for addr in self {
+            let addr = addr?;
+            canonname = canonname.or(addr.canonname);
+            match groups.iter_mut().find(|g| g.sockaddr == addr.sockaddr) {
+                Some(group) => group.socktypes.push((addr.socktype, addr.protocol)),
+                None => groups.push(GroupedAddrInfo {
+                    address: addr.address,
+                    sockaddr: addr.sockaddr,
+                    socktypes: vec![(addr.socktype, addr.protocol)],
+                    canonname: None,
+                }),
             }
-            Some(ret)
         }
+        for group in &mut groups {
+            group.canonname.clone_from(&canonname);
+        }
+        Ok(groups)
     }
 }
 
+/// Socket information for a single address, with every socktype returned
+/// for it. Created by `AddrInfoIter::group_by_address`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GroupedAddrInfo {
+    /// Address family for this socket. `ai_family` in libc.
+    ///
+    /// Values are defined by the libc on your system.
+    pub address: i32,
+    /// Socket address shared by every entry in this group.
+    pub sockaddr: SocketAddr,
+    /// Each `(socktype, protocol)` pair returned for this address.
+    /// `ai_socktype` and `ai_protocol` in libc.
+    ///
+    /// Values are defined by the libc on your system.
+    pub socktypes: Vec<(i32, i32)>,
+    /// If requested, the canonical name for the host. libc only sets this on
+    /// the first result, so it is copied to every group.
+    pub canonname: Option<String>,
+}
+
 impl FusedIterator for AddrInfoIter {}
+
+/// An iterator of `RawAddrInfo` structs, including every address family.
+/// Created by `AddrInfoIter::raw`.
+pub struct RawAddrInfoIter(AddrInfoIter);
+
+impl Iterator for RawAddrInfoIter {
+    type Item = io::Result<RawAddrInfo>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let ptr = self.0.next_ptr()?;
+        Some(unsafe { RawAddrInfo::from_ptr(ptr) })
+    }
+}
+
+impl FusedIterator for RawAddrInfoIter {}
 unsafe impl Sync for AddrInfoIter {}
 unsafe impl Send for AddrInfoIter {}
 
 impl Drop for AddrInfoIter {
     fn drop(&mut self) {
-        unsafe { c_freeaddrinfo(self.orig) }
+        if !self.orig.is_null() {
+            unsafe { c_freeaddrinfo(self.orig) }
+        }
     }
 }
 
@@ -203,22 +474,73 @@ impl Drop for AddrInfoIter {
 /// wrapper around the libc getaddrinfo.
 ///
 /// The only portable way to support International Domain Names (UTF8 DNS
-/// names) is to manually convert to puny code before calling this function -
+/// names) is to manually convert to Punycode before calling this function -
 /// which can be done using the `idna` crate. However some libc backends may
 /// support this natively, or by using bitflags in the hints argument.
 ///
-/// Resolving names from non-UTF8 locales is currently not supported (as the
-/// interface uses &str). Raise an issue if this is a concern for you.
+/// With the `idna` feature enabled, hosts are converted to Punycode using
+/// UTS #46 processing before the lookup. Rejected hosts return a
+/// `LookupErrorKind::Idna` error.
+///
+/// To resolve names from non-UTF8 locales, use `getaddrinfo_bytes`.
 pub fn getaddrinfo(
     host: Option<&str>,
     service: Option<&str>,
     hints: Option<AddrInfoHints>,
+) -> Result<AddrInfoIter, LookupError> {
+    // Errors record the host as the caller gave it, not its Punycode form.
+    let with_query =
+        |e: LookupError| e.with_query(host.map(str::as_bytes), service.map(str::as_bytes), hints);
+
+    #[cfg(feature = "idna")]
+    let ascii = host
+        .map(crate::idn::to_ascii)
+        .transpose()
+        .map_err(with_query)?;
+    #[cfg(feature = "idna")]
+    let host = ascii.as_deref();
+
+    raw_getaddrinfo(host.map(str::as_bytes), service.map(str::as_bytes), hints).map_err(with_query)
+}
+
+/// Retrieve socket information for a host, service, or both, given as raw
+/// bytes. Otherwise identical to `getaddrinfo`, except that the `idna`
+/// feature does not apply.
+///
+/// This supports names that aren't valid UTF8, such as those from non-UTF8
+/// locales. On unix, an `OsStr` can be passed using
+/// `std::os::unix::ffi::OsStrExt::as_bytes`. Canonical names that aren't
+/// valid UTF8 are returned as an error by the iterator. Use
+/// `AddrInfoIter::raw` to get them as bytes instead.
+pub fn getaddrinfo_bytes(
+    host: Option<&[u8]>,
+    service: Option<&[u8]>,
+    hints: Option<AddrInfoHints>,
+) -> Result<AddrInfoIter, LookupError> {
+    raw_getaddrinfo(host, service, hints).map_err(|e| e.with_query(host, service, hints))
+}
+
+fn raw_getaddrinfo(
+    host: Option<&[u8]>,
+    service: Option<&[u8]>,
+    hints: Option<AddrInfoHints>,
 ) -> Result<AddrInfoIter, LookupError> {
     // We must have at least host or service.
     if host.is_none() && service.is_none() {
         Err(io::Error::other("Either host or service must be supplied"))?;
     }
 
+    // libc rejects a request for the canonical name without a host, but
+    // not all platforms report why.
+    if host.is_none()
+        && hints.is_some_and(|h| AddrInfoFlags::from(h.flags).contains(AddrInfoFlags::CANONNAME))
+    {
+        Err(LookupError::invalid(
+            LookupErrorKind::Badflags,
+            "AI_CANONNAME requires a host to be supplied",
+        ))?;
+    }
+
     // Allocate CStrings, and keep around to free.
     let host = match host {
         Some(host_str) => Some(CString::new(host_str)?),
@@ -244,9 +566,8 @@ pub fn getaddrinfo(
     #[cfg(windows)]
     crate::win::init_winsock();
 
-    unsafe {
-        LookupError::match_gai_error(c_getaddrinfo(c_host, c_service, &c_hints, &mut res))?;
-    }
+    let err = unsafe { c_getaddrinfo(c_host, c_service, &c_hints, &mut res) };
+    LookupError::match_gai_and_errno(err, last_errno())?;
 
     Ok(AddrInfoIter {
         orig: res,
@@ -255,9 +576,161 @@ pub fn getaddrinfo(
 }
 
 #[test]
-fn test_addrinfohints() {
-    use crate::{AddrFamily, SockType};
+fn test_group_by_address() {
+    let hints = AddrInfoHints {
+        flags: AddrInfoFlags::CANONNAME.into(),
+        ..AddrInfoHints::default()
+    };
+    let count = getaddrinfo(Some("localhost"), None, Some(hints))
+        .unwrap()
+        .count();
+    let groups = getaddrinfo(Some("localhost"), None, Some(hints))
+        .unwrap()
+        .group_by_address()
+        .unwrap();
+
+    assert_eq!(
+        groups.iter().map(|g| g.socktypes.len()).sum::<usize>(),
+        count
+    );
+    for (i, group) in groups.iter().enumerate() {
+        assert!(groups[i + 1..].iter().all(|g| g.sockaddr != group.sockaddr));
+        assert!(group.socktypes.iter().any(|(s, _)| *s == SockType::Stream));
+        assert_eq!(group.canonname, groups[0].canonname);
+    }
+    assert!(groups[0].canonname.is_some());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_addrinfo_unix_family() {
+    let sockaddr = SockAddr::unix("/tmp/dns-lookup.sock").unwrap();
+    let mut c_addr: c_addrinfo = unsafe { mem::zeroed() };
+    c_addr.ai_family = AddrFamily::Unix.into();
+    c_addr.ai_socktype = SockType::Stream.into();
+    c_addr.ai_addrlen = sockaddr.len();
+    c_addr.ai_addr = sockaddr.as_ptr() as *mut _;
+
+    let raw = unsafe { RawAddrInfo::from_ptr(&mut c_addr) }.unwrap();
+    assert_eq!(raw.sockaddr, sockaddr);
+    assert_eq!(AddrFamily::try_from(raw.address).unwrap(), AddrFamily::Unix);
+    assert!(AddrInfo::try_from(raw).is_err());
+
+    // AddrInfoIter skips the entry, and the raw iterator returns it.
+    let c_addr: *mut c_addrinfo = &mut c_addr;
+    let iter = || AddrInfoIter {
+        orig: ptr::null_mut(),
+        cur: c_addr,
+    };
+    assert_eq!(iter().count(), 0);
+    let raw = iter().raw().collect::<io::Result<Vec<_>>>().unwrap();
+    assert_eq!(raw[0].sockaddr, sockaddr);
+}
+
+#[test]
+fn test_addrinfo_canonname_bytes() {
+    let sockaddr = SockAddr::from(SocketAddr::from(([127, 0, 0, 1], 80)));
+    let canonname = b"caf\xe9.example\0";
+    let mut c_addr: c_addrinfo = unsafe { mem::zeroed() };
+    c_addr.ai_family = AddrFamily::Inet.into();
+    c_addr.ai_addrlen = sockaddr.len() as _;
+    c_addr.ai_addr = sockaddr.as_ptr() as *mut _;
+    c_addr.ai_canonname = canonname.as_ptr() as *mut _;
+    let c_addr: *mut c_addrinfo = &mut c_addr;
+    let iter = || AddrInfoIter {
+        orig: ptr::null_mut(),
+        cur: c_addr,
+    };
+
+    assert!(iter().next().unwrap().is_err());
+    let raw = iter().raw().next().unwrap().unwrap();
+    assert_eq!(
+        raw.canonname.as_deref(),
+        Some(&canonname[..canonname.len() - 1])
+    );
+}
+
+#[test]
+fn test_addrinfo_accessors() {
+    let hints = AddrInfoHints::builder()
+        .family(AddrFamily::Inet)
+        .socktype(SockType::Stream)
+        .build()
+        .unwrap();
+    for addr in getaddrinfo(Some("localhost"), None, Some(hints)).unwrap() {
+        let addr = addr.unwrap();
+        assert_eq!(addr.family().unwrap(), AddrFamily::Inet);
+        assert_eq!(addr.sock_type().unwrap(), SockType::Stream);
+        assert_eq!(addr.protocol().unwrap(), Protocol::TCP);
+    }
+}
 
+#[test]
+fn test_getaddrinfo_bytes() {
+    let addrs = getaddrinfo_bytes(Some(b"localhost"), Some(b"ssh"), None)
+        .unwrap()
+        .collect::<io::Result<Vec<_>>>()
+        .unwrap();
+    let expected = getaddrinfo(Some("localhost"), Some("ssh"), None)
+        .unwrap()
+        .collect::<io::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(addrs, expected);
+
+    // Interior nul bytes can't be passed to libc.
+    assert!(getaddrinfo_bytes(Some(b"local\0host"), None, None).is_err());
+}
+
+#[test]
+fn test_addrinfohints_builder() {
+    let hints = AddrInfoHints::builder()
+        .family(AddrFamily::Inet6)
+        .socktype(SockType::DGram)
+        .protocol(Protocol::UDP)
+        .flags(AddrInfoFlags::CANONNAME)
+        .build()
+        .unwrap();
+    assert_eq!(
+        hints,
+        AddrInfoHints::new(
+            Some(AddrInfoFlags::CANONNAME.into()),
+            Some(AddrFamily::Inet6),
+            Some(SockType::DGram),
+            Some(Protocol::UDP)
+        )
+    );
+    assert_eq!(
+        AddrInfoHints::builder().build().unwrap(),
+        AddrInfoHints::default()
+    );
+
+    let err = AddrInfoHints::builder()
+        .socktype(SockType::Stream)
+        .protocol(Protocol::UDP)
+        .build()
+        .unwrap_err();
+    assert!(matches!(err.kind(), LookupErrorKind::Socktype));
+
+    // ICMP is only accepted by getaddrinfo with raw sockets.
+    let err = AddrInfoHints::builder()
+        .socktype(SockType::DGram)
+        .protocol(Protocol::ICMP)
+        .build()
+        .unwrap_err();
+    assert!(matches!(err.kind(), LookupErrorKind::Socktype));
+    #[cfg(not(target_os = "redox"))]
+    assert!(AddrInfoHints::builder()
+        .socktype(SockType::Raw)
+        .protocol(Protocol::ICMP)
+        .build()
+        .is_ok());
+
+    let err = getaddrinfo(None, Some("ssh"), Some(hints)).err().unwrap();
+    assert!(matches!(err.kind(), LookupErrorKind::Badflags));
+}
+
+#[test]
+fn test_addrinfohints() {
     assert_eq!(
         AddrInfoHints {
             flags: 1,
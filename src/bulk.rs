@@ -0,0 +1,290 @@
+use std::io;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::addrinfo::getaddrinfo;
+use crate::err::LookupError;
+use crate::lookup::{host_hints, reload_dns_nameserver};
+
+/// Result of resolving a single name with `resolve_many`.
+pub type ResolveResult = Result<Vec<IpAddr>, LookupError>;
+
+/// Overall progress of a `resolve_many` run.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Progress {
+    /// Number of names that have finished, successfully or not.
+    pub completed: usize,
+    /// Total number of names being resolved.
+    pub total: usize,
+}
+
+/// A pending bulk resolution, created by `resolve_many`.
+///
+/// Nothing is resolved until the results are requested, either in input
+/// order with `ordered`, or in completion order by iterating.
+pub struct ResolveMany {
+    names: Vec<String>,
+    concurrency: usize,
+    deadline: Option<Instant>,
+    progress: Option<Box<dyn FnMut(Progress)>>,
+}
+
+/// Resolve many hostnames, with at most `concurrency` lookups in flight at
+/// once.
+///
+/// Each name is resolved like `lookup_host`. Use the returned `ResolveMany`
+/// to set a deadline or progress callback, then collect the results.
+///
+/// ```rust
+///   use dns_lookup::resolve_many;
+///   use std::time::Duration;
+///
+///   let results = resolve_many(["localhost", "127.0.0.1"], 4)
+///     .timeout(Duration::from_secs(10))
+///     .ordered();
+///   assert_eq!(results.len(), 2);
+/// ```
+pub fn resolve_many<I, S>(names: I, concurrency: usize) -> ResolveMany
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    ResolveMany {
+        names: names.into_iter().map(Into::into).collect(),
+        concurrency: concurrency.max(1),
+        deadline: None,
+        progress: None,
+    }
+}
+
+impl ResolveMany {
+    /// Stop waiting for lookups at the given instant. Names that haven't
+    /// finished by then fail with an `io::ErrorKind::TimedOut` error.
+    ///
+    /// Lookups already in progress can't be cancelled, and finish in the
+    /// background.
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Set a deadline relative to now. See `deadline`.
+    pub fn timeout(self, timeout: Duration) -> Self {
+        self.deadline(Instant::now() + timeout)
+    }
+
+    /// Call the given function each time a name finishes resolving.
+    pub fn on_progress<F: FnMut(Progress) + 'static>(mut self, f: F) -> Self {
+        self.progress = Some(Box::new(f));
+        self
+    }
+
+    /// Resolve all names, returning results in the same order as the input.
+    pub fn ordered(self) -> Vec<ResolveResult> {
+        let mut results = (0..self.names.len()).map(|_| None).collect::<Vec<_>>();
+        for (idx, res) in self {
+            results[idx] = Some(res);
+        }
+        results.into_iter().map(Option::unwrap).collect()
+    }
+}
+
+impl IntoIterator for ResolveMany {
+    type Item = (usize, ResolveResult);
+    type IntoIter = Completions;
+
+    /// Start resolving, yielding `(input index, result)` pairs in the order
+    /// lookups complete.
+    fn into_iter(self) -> Completions {
+        let total = self.names.len();
+        let names = Arc::new(self.names);
+        let next = Arc::new(AtomicUsize::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = mpsc::channel();
+
+        for _ in 0..self.concurrency.min(total) {
+            let worker = Worker {
+                names: names.clone(),
+                next: next.clone(),
+                stop: stop.clone(),
+                deadline: self.deadline,
+            };
+            let tx = tx.clone();
+            thread::spawn(move || {
+                while let Some((idx, name)) = worker.next_name() {
+                    if tx.send((idx, resolve(name))).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        Completions {
+            rx,
+            stop,
+            deadline: self.deadline,
+            progress: self.progress,
+            done: vec![false; total],
+            unfinished: Vec::new(),
+            completed: 0,
+        }
+    }
+}
+
+/// Iterator of `resolve_many` results in completion order, yielding the
+/// index of each name in the input alongside its result.
+pub struct Completions {
+    rx: Receiver<(usize, ResolveResult)>,
+    stop: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+    progress: Option<Box<dyn FnMut(Progress)>>,
+    done: Vec<bool>,
+    unfinished: Vec<(usize, ResolveResult)>,
+    completed: usize,
+}
+
+impl Completions {
+    // Give up on every name that hasn't completed, failing it with the
+    // given error.
+    fn abandon(&mut self, kind: io::ErrorKind, msg: &str) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.unfinished = self
+            .done
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, done)| !**done)
+            .map(|(idx, _)| (idx, Err(io::Error::new(kind, msg).into())))
+            .collect();
+    }
+}
+
+impl Iterator for Completions {
+    type Item = (usize, ResolveResult);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let total = self.done.len();
+        if self.completed == total {
+            return None;
+        }
+
+        if self.unfinished.is_empty() {
+            let received = match self.deadline {
+                Some(d) => self
+                    .rx
+                    .recv_timeout(d.saturating_duration_since(Instant::now())),
+                None => self.rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            // Workers stop claiming names once the deadline passes, so they
+            // may all exit before the receive times out.
+            let expired = self.deadline.is_some_and(|d| Instant::now() >= d);
+            match received {
+                Ok((idx, res)) => self.unfinished.push((idx, res)),
+                Err(RecvTimeoutError::Timeout) => self.abandon(
+                    io::ErrorKind::TimedOut,
+                    "Deadline exceeded before lookup completed",
+                ),
+                Err(RecvTimeoutError::Disconnected) if expired => self.abandon(
+                    io::ErrorKind::TimedOut,
+                    "Deadline exceeded before lookup completed",
+                ),
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.abandon(io::ErrorKind::Other, "Lookup worker exited unexpectedly")
+                }
+            }
+        }
+
+        let (idx, res) = self.unfinished.pop()?;
+        self.done[idx] = true;
+        self.completed += 1;
+        if let Some(progress) = self.progress.as_mut() {
+            progress(Progress {
+                completed: self.completed,
+                total,
+            });
+        }
+        Some((idx, res))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.done.len() - self.completed;
+        (remaining, Some(remaining))
+    }
+}
+
+impl Drop for Completions {
+    fn drop(&mut self) {
+        // Don't let workers start lookups nobody will read.
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+// Shared state for a thread resolving names from a `resolve_many` run.
+struct Worker {
+    names: Arc<Vec<String>>,
+    next: Arc<AtomicUsize>,
+    stop: Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+impl Worker {
+    // Claim the next unresolved name, unless the run has been stopped or
+    // the deadline has passed.
+    fn next_name(&self) -> Option<(usize, &str)> {
+        if self.stop.load(Ordering::Relaxed) || self.deadline.is_some_and(|d| Instant::now() >= d) {
+            return None;
+        }
+        let idx = self.next.fetch_add(1, Ordering::Relaxed);
+        self.names.get(idx).map(|name| (idx, &name[..]))
+    }
+}
+
+fn resolve(name: &str) -> ResolveResult {
+    match getaddrinfo(Some(name), None, Some(host_hints())) {
+        Ok(addrs) => Ok(addrs.collect_ips()?),
+        Err(e) => {
+            reload_dns_nameserver();
+            Err(e)
+        }
+    }
+}
+
+#[test]
+fn test_resolve_many() {
+    use std::sync::Mutex;
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let progress = seen.clone();
+    let results = resolve_many(vec!["localhost"; 5], 2)
+        .on_progress(move |p| progress.lock().unwrap().push(p))
+        .ordered();
+
+    assert_eq!(results.len(), 5);
+    for res in results {
+        assert!(res.unwrap().contains(&"127.0.0.1".parse().unwrap()));
+    }
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 5);
+    assert_eq!(
+        seen.last(),
+        Some(&Progress {
+            completed: 5,
+            total: 5
+        })
+    );
+
+    // A deadline that has already passed fails every name.
+    let results = resolve_many(["localhost", "localhost"], 2)
+        .deadline(Instant::now())
+        .into_iter()
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 2);
+    for (_, res) in results {
+        let err: io::Error = res.unwrap_err().into();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+}
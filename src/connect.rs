@@ -0,0 +1,234 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::net::{SocketAddr, TcpStream};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use crate::addrinfo::getaddrinfo;
+use crate::lookup::{host_hints, interleave, reload_dns_nameserver};
+
+/// A connection attempt made by `connect_tcp` that failed.
+#[derive(Debug)]
+pub struct FailedAttempt {
+    /// Address the connection was attempted to.
+    pub addr: SocketAddr,
+    /// Error returned by the attempt.
+    pub error: io::Error,
+}
+
+/// A successful connection made by `connect_tcp`.
+#[derive(Debug)]
+pub struct Connection {
+    /// The first stream to successfully connect.
+    pub stream: TcpStream,
+    /// Attempts that failed before the connection succeeded. Attempts that
+    /// were still in progress are abandoned, and not included.
+    pub failed: Vec<FailedAttempt>,
+}
+
+/// The error returned by `connect_tcp` when every connection attempt fails.
+///
+/// This is returned as the payload of an `io::Error`, with the kind of the
+/// last failed attempt. Use `ConnectError::from_io_error` to get the
+/// individual attempts.
+#[derive(Debug)]
+pub struct ConnectError {
+    attempts: Vec<FailedAttempt>,
+}
+
+impl ConnectError {
+    /// Recover the `ConnectError` from an `io::Error` returned by
+    /// `connect_tcp`. Returns `None` for other errors, such as a failed
+    /// lookup.
+    pub fn from_io_error(err: &io::Error) -> Option<&ConnectError> {
+        err.get_ref()?.downcast_ref()
+    }
+
+    /// Every attempt that was made, in the order they failed. Empty if the
+    /// host had no addresses to connect to.
+    pub fn attempts(&self) -> &[FailedAttempt] {
+        &self.attempts
+    }
+
+    /// Convert into the attempts that were made.
+    pub fn into_attempts(self) -> Vec<FailedAttempt> {
+        self.attempts
+    }
+}
+
+impl fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.attempts.is_empty() {
+            return f.write_str("No addresses to connect to");
+        }
+        f.write_str("All connection attempts failed: ")?;
+        for (idx, attempt) in self.attempts.iter().enumerate() {
+            if idx > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{}: {}", attempt.addr, attempt.error)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ConnectError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.attempts.last().map(|a| &a.error as _)
+    }
+}
+
+/// Options for connecting with Happy Eyeballs (RFC 8305).
+///
+/// If every attempt fails, the returned `io::Error` holds a `ConnectError`
+/// listing each attempt.
+///
+/// ```rust,no_run
+///   use dns_lookup::HappyEyeballs;
+///   use std::time::Duration;
+///
+///   let conn = HappyEyeballs::new()
+///     .attempt_delay(Duration::from_millis(100))
+///     .connect("example.com", 80)
+///     .unwrap();
+///   println!("Connected to {:?}", conn.stream.peer_addr());
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct HappyEyeballs {
+    attempt_delay: Duration,
+    connect_timeout: Option<Duration>,
+}
+
+impl Default for HappyEyeballs {
+    fn default() -> Self {
+        HappyEyeballs {
+            attempt_delay: Duration::from_millis(250),
+            connect_timeout: None,
+        }
+    }
+}
+
+impl HappyEyeballs {
+    /// Create options with the RFC 8305 recommended attempt delay of 250ms.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Time to wait for an attempt before starting the next one in
+    /// parallel. A failed attempt starts the next one immediately.
+    pub fn attempt_delay(mut self, delay: Duration) -> Self {
+        self.attempt_delay = delay;
+        self
+    }
+
+    /// Timeout for each individual connection attempt. By default the
+    /// operating system's connect timeout is used.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Resolve `host`, and connect to the first address that accepts a TCP
+    /// connection on `port`.
+    pub fn connect(&self, host: &str, port: u16) -> io::Result<Connection> {
+        let addrs = match getaddrinfo(Some(host), None, Some(host_hints())) {
+            Ok(addrs) => addrs
+                .collect_ips()?
+                .into_iter()
+                .map(|ip| SocketAddr::new(ip, port))
+                .collect(),
+            Err(e) => {
+                reload_dns_nameserver();
+                Err(e)?
+            }
+        };
+        self.connect_addrs(interleave(addrs, SocketAddr::is_ipv6))
+    }
+
+    // Race connections to the given addresses, staggering the start of
+    // each attempt.
+    fn connect_addrs(&self, addrs: Vec<SocketAddr>) -> io::Result<Connection> {
+        let (tx, rx) = mpsc::channel();
+        let mut candidates = addrs.into_iter();
+        let mut pending = 0;
+        let mut failed = Vec::new();
+
+        loop {
+            if let Some(addr) = candidates.next() {
+                let tx = tx.clone();
+                let timeout = self.connect_timeout;
+                thread::spawn(move || {
+                    let res = match timeout {
+                        Some(timeout) => TcpStream::connect_timeout(&addr, timeout),
+                        None => TcpStream::connect(addr),
+                    };
+                    // The receiver is gone if another attempt already won.
+                    let _ = tx.send((addr, res));
+                });
+                pending += 1;
+            } else if pending == 0 {
+                break;
+            }
+
+            // Wait for the attempt delay if there are more addresses to
+            // try, otherwise until an outstanding attempt finishes.
+            let received = if candidates.len() > 0 {
+                rx.recv_timeout(self.attempt_delay).ok()
+            } else {
+                rx.recv().ok()
+            };
+            match received {
+                Some((_, Ok(stream))) => return Ok(Connection { stream, failed }),
+                Some((addr, Err(error))) => {
+                    pending -= 1;
+                    failed.push(FailedAttempt { addr, error });
+                }
+                None => {}
+            }
+        }
+
+        Err(all_failed(failed))
+    }
+}
+
+/// Resolve `host`, and connect to `port` using Happy Eyeballs (RFC 8305)
+/// with the default options.
+///
+/// IPv6 and IPv4 addresses are interleaved, and a new connection attempt is
+/// started every 250ms until one succeeds. Use `HappyEyeballs` to change
+/// the delay.
+pub fn connect_tcp(host: &str, port: u16) -> io::Result<Connection> {
+    HappyEyeballs::new().connect(host, port)
+}
+
+// Wrap every failed attempt into a single error, keeping the kind of the
+// last failure.
+fn all_failed(failed: Vec<FailedAttempt>) -> io::Error {
+    let kind = failed
+        .last()
+        .map_or(io::ErrorKind::NotFound, |f| f.error.kind());
+    io::Error::new(kind, ConnectError { attempts: failed })
+}
+
+#[test]
+fn test_connect_tcp() {
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let conn = connect_tcp("localhost", addr.port()).unwrap();
+    assert_eq!(conn.stream.peer_addr().unwrap(), addr);
+
+    // Nothing listens on the closed port, so every attempt fails.
+    drop(listener);
+    let err = HappyEyeballs::new()
+        .attempt_delay(Duration::from_millis(10))
+        .connect("localhost", addr.port())
+        .unwrap_err();
+    let attempts = ConnectError::from_io_error(&err).unwrap().attempts();
+    assert!(attempts.iter().any(|a| a.addr == addr));
+    assert_eq!(err.kind(), attempts.last().unwrap().error.kind());
+}
@@ -0,0 +1,304 @@
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::str::FromStr;
+
+// Maximum lengths from RFC 1035 section 2.3.4. The 255 octet wire format
+// limit leaves 253 characters for the text form, without a trailing dot.
+const MAX_LABEL_LEN: usize = 63;
+const MAX_NAME_LEN: usize = 253;
+
+/// Which characters are allowed in the labels of a `DomainName`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DomainMode {
+    /// Letters, digits and hyphens, not starting or ending with a hyphen
+    /// (RFC 1123 section 2.1).
+    #[default]
+    Strict,
+    /// Like `Strict`, but also allows underscores, as used in SRV and
+    /// other service records (`_sip._tcp.example.com`).
+    Permissive,
+}
+
+/// A validated domain name.
+///
+/// Labels are checked to be at most 63 characters, and the whole name at
+/// most 253 characters. The root name is written as `.`, and has no
+/// labels. Names compare and hash case-insensitively, and a trailing dot is
+/// ignored. The name dereferences to `&str`, so it can be passed directly
+/// to `lookup_host` and friends.
+///
+/// ```rust
+///   use dns_lookup::{lookup_host, DomainName};
+///
+///   let name: DomainName = "localhost".parse().unwrap();
+///   assert_eq!(name, "LocalHost.".parse().unwrap());
+///
+///   let ips = lookup_host(&name).unwrap().collect::<Vec<_>>();
+///   assert!(ips.contains(&"127.0.0.1".parse().unwrap()));
+/// ```
+#[derive(Clone, Debug)]
+pub struct DomainName {
+    name: String,
+}
+
+impl DomainName {
+    /// Validate a domain name using `DomainMode::Strict`.
+    pub fn new(name: &str) -> Result<Self, DomainNameError> {
+        DomainName::with_mode(name, DomainMode::Strict)
+    }
+
+    /// Validate a domain name using the given mode.
+    pub fn with_mode(name: &str, mode: DomainMode) -> Result<Self, DomainNameError> {
+        if name == "." {
+            return Ok(DomainName {
+                name: name.to_owned(),
+            });
+        }
+        let relative = name.strip_suffix('.').unwrap_or(name);
+        if relative.is_empty() {
+            return Err(DomainNameError::new("Domain name is empty"));
+        }
+        if relative.len() > MAX_NAME_LEN {
+            return Err(DomainNameError::new(format!(
+                "Domain name is {} characters, longer than {MAX_NAME_LEN}",
+                relative.len()
+            )));
+        }
+        for label in relative.split('.') {
+            check_label(label.as_bytes(), mode)?;
+        }
+        Ok(DomainName {
+            name: name.to_owned(),
+        })
+    }
+
+    /// Parse an uncompressed domain name in DNS wire format: a sequence of
+    /// length-prefixed labels, ending with an empty label. The result is
+    /// always fully qualified.
+    pub fn from_wire(wire: &[u8], mode: DomainMode) -> Result<Self, DomainNameError> {
+        let mut name = String::new();
+        let mut rest = wire;
+        loop {
+            let Some((&len, tail)) = rest.split_first() else {
+                return Err(DomainNameError::new("Wire format name is truncated"));
+            };
+            let len = usize::from(len);
+            if len == 0 {
+                rest = tail;
+                break;
+            }
+            if len > MAX_LABEL_LEN {
+                return Err(DomainNameError::new(
+                    "Wire format label is too long, or a compression pointer",
+                ));
+            }
+            let label = tail
+                .get(..len)
+                .ok_or_else(|| DomainNameError::new("Wire format name is truncated"))?;
+            check_label(label, mode)?;
+            // Labels are checked to be ASCII above.
+            name.push_str(std::str::from_utf8(label).unwrap_or_default());
+            name.push('.');
+            rest = &tail[len..];
+        }
+        if !rest.is_empty() {
+            return Err(DomainNameError::new(
+                "Unexpected data after wire format name",
+            ));
+        }
+        if name.is_empty() {
+            name.push('.');
+        }
+        DomainName::with_mode(&name, mode)
+    }
+
+    /// Encode this name in DNS wire format.
+    pub fn to_wire(&self) -> Vec<u8> {
+        let mut wire = Vec::with_capacity(self.relative().len() + 2);
+        for label in self.labels() {
+            wire.push(label.len() as u8);
+            wire.extend_from_slice(label.as_bytes());
+        }
+        wire.push(0);
+        wire
+    }
+
+    /// The name as given, including any trailing dot.
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the name is fully qualified, ending with a dot.
+    pub fn is_fqdn(&self) -> bool {
+        self.name.ends_with('.')
+    }
+
+    /// Whether this is the root name, `.`.
+    pub fn is_root(&self) -> bool {
+        self.name == "."
+    }
+
+    /// The labels of this name, from left to right. The root name has no
+    /// labels.
+    pub fn labels(&self) -> impl Iterator<Item = &str> {
+        // Only the root name has an empty label.
+        self.relative().split('.').filter(|label| !label.is_empty())
+    }
+
+    // The name without any trailing dot.
+    fn relative(&self) -> &str {
+        self.name.strip_suffix('.').unwrap_or(&self.name)
+    }
+}
+
+fn check_label(label: &[u8], mode: DomainMode) -> Result<(), DomainNameError> {
+    let printable = String::from_utf8_lossy(label);
+    if label.is_empty() {
+        return Err(DomainNameError::new("Domain name contains an empty label"));
+    }
+    if label.len() > MAX_LABEL_LEN {
+        return Err(DomainNameError::new(format!(
+            "Label '{printable}' is {} characters, longer than {MAX_LABEL_LEN}",
+            label.len()
+        )));
+    }
+    if label.first() == Some(&b'-') || label.last() == Some(&b'-') {
+        return Err(DomainNameError::new(format!(
+            "Label '{printable}' starts or ends with a hyphen"
+        )));
+    }
+    let allowed = |c: &u8| {
+        c.is_ascii_alphanumeric() || *c == b'-' || (mode == DomainMode::Permissive && *c == b'_')
+    };
+    if !label.iter().all(allowed) {
+        return Err(DomainNameError::new(format!(
+            "Label '{printable}' contains invalid characters"
+        )));
+    }
+    Ok(())
+}
+
+impl PartialEq for DomainName {
+    fn eq(&self, other: &Self) -> bool {
+        self.relative().eq_ignore_ascii_case(other.relative())
+    }
+}
+
+impl Eq for DomainName {}
+
+impl Hash for DomainName {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for b in self.relative().bytes() {
+            state.write_u8(b.to_ascii_lowercase());
+        }
+    }
+}
+
+impl FromStr for DomainName {
+    type Err = DomainNameError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        DomainName::new(name)
+    }
+}
+
+impl fmt::Display for DomainName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl Deref for DomainName {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.name
+    }
+}
+
+impl AsRef<str> for DomainName {
+    fn as_ref(&self) -> &str {
+        &self.name
+    }
+}
+
+impl From<DomainName> for String {
+    fn from(name: DomainName) -> String {
+        name.name
+    }
+}
+
+/// An error found while validating a `DomainName`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DomainNameError {
+    msg: String,
+}
+
+impl DomainNameError {
+    fn new<M: Into<String>>(msg: M) -> Self {
+        DomainNameError { msg: msg.into() }
+    }
+}
+
+impl fmt::Display for DomainNameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.msg)
+    }
+}
+
+impl Error for DomainNameError {}
+
+#[test]
+fn test_domain_name() {
+    use std::collections::HashSet;
+
+    let name = DomainName::new("Example.COM.").unwrap();
+    assert!(name.is_fqdn());
+    assert_eq!(name.labels().collect::<Vec<_>>(), ["Example", "COM"]);
+    assert_eq!(name, DomainName::new("example.com").unwrap());
+    let set = HashSet::from([name.clone()]);
+    assert!(set.contains(&DomainName::new("EXAMPLE.com").unwrap()));
+
+    // RFC 1123 allows labels to start with a digit.
+    assert!(DomainName::new("3com.example").is_ok());
+    assert!(DomainName::new("").is_err());
+    assert!(DomainName::new("..").is_err());
+    assert!(DomainName::new("a..b").is_err());
+    assert!(DomainName::new("-a.example").is_err());
+    assert!(DomainName::new("a-.example").is_err());
+    assert!(DomainName::new("a b.example").is_err());
+    assert!(DomainName::new("_sip._tcp.example").is_err());
+    assert!(DomainName::with_mode("_sip._tcp.example", DomainMode::Permissive).is_ok());
+
+    let label = "a".repeat(63);
+    assert!(DomainName::new(&label).is_ok());
+    assert!(DomainName::new(&format!("{label}a")).is_err());
+    let long = [&label[..]; 4].join(".");
+    assert_eq!(long.len(), 255);
+    assert!(DomainName::new(&long[2..]).is_ok());
+    assert!(DomainName::new(&long[1..]).is_err());
+}
+
+#[test]
+fn test_domain_name_wire() {
+    let name = DomainName::new("www.example.com").unwrap();
+    let wire = name.to_wire();
+    assert_eq!(wire, b"\x03www\x07example\x03com\x00");
+
+    let parsed = DomainName::from_wire(&wire, DomainMode::Strict).unwrap();
+    assert_eq!(parsed.as_str(), "www.example.com.");
+    assert_eq!(parsed, name);
+
+    assert!(DomainName::from_wire(b"\x03www", DomainMode::Strict).is_err());
+    assert!(DomainName::from_wire(b"\x03www\x00\x00", DomainMode::Strict).is_err());
+    assert!(DomainName::from_wire(b"\xc0\x0c", DomainMode::Strict).is_err());
+
+    let root = DomainName::from_wire(b"\x00", DomainMode::Strict).unwrap();
+    assert!(root.is_root() && root.is_fqdn());
+    assert_eq!(root, DomainName::new(".").unwrap());
+    assert_eq!(root.labels().count(), 0);
+    assert_eq!(root.to_wire(), b"\x00");
+}
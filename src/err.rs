@@ -1,15 +1,28 @@
+use std::error::Error;
 use std::ffi;
+use std::fmt;
 use std::io;
+
+use crate::addrinfo::AddrInfoHints;
 #[cfg(unix)]
 use {std::os::raw::c_char, std::str};
 
 /// Struct that stores a lookup error from `getaddrinfo`
 /// or `getnameinfo`. Can automatically be coerced to an io::Error using `?`.
+/// The resulting `io::Error` has a matching `io::ErrorKind`, and wraps this
+/// error so it can be recovered with `LookupError::from_io_error`.
+///
+/// Errors from `getaddrinfo` record the host, service and hints that were
+/// queried, and errors from `getnameinfo` the address and port, which are
+/// included when the error is displayed.
 #[derive(Debug)]
 pub struct LookupError {
     kind: LookupErrorKind,
     err_num: i32,
     inner: io::Error,
+    host: Option<String>,
+    service: Option<String>,
+    hints: Option<AddrInfoHints>,
 }
 
 impl LookupError {
@@ -23,15 +36,63 @@ impl LookupError {
         }
     }
 
+    /// Match a `gai` error like `match_gai_error`, using an `errno`
+    /// captured right after the failing call.
+    pub(crate) fn match_gai_and_errno(err: i32, errno: i32) -> Result<(), Self> {
+        match err {
+            0 => Ok(()),
+            _ => Err(LookupError::from_gai_and_errno(err, errno)),
+        }
+    }
+
     /// Create a new LookupError from a `gai` error,
     /// returned by `getaddrinfo` and `getnameinfo`.
+    ///
+    /// For `EAI_SYSTEM`, the current `errno` is used, which may have been
+    /// overwritten since the failing call. Prefer `from_gai_and_errno`.
     pub fn new(err: i32) -> Self {
+        LookupError::from_gai_and_errno(err, last_errno())
+    }
+
+    /// Create a new LookupError from a `gai` error, and the `errno` (or
+    /// `WSAGetLastError` on windows) read right after the failing call.
+    /// The `errno` is only used for `EAI_SYSTEM` errors on unix.
+    pub fn from_gai_and_errno(err: i32, errno: i32) -> Self {
         LookupError {
             kind: LookupErrorKind::new(err),
             err_num: err,
-            inner: gai_err_to_io_err(err),
+            inner: gai_err_to_io_err(err, errno),
+            host: None,
+            service: None,
+            hints: None,
         }
     }
+    /// Create a LookupError for invalid arguments, caught before calling
+    /// `getaddrinfo` or `getnameinfo`.
+    pub(crate) fn invalid<M: Into<String>>(kind: LookupErrorKind, msg: M) -> Self {
+        LookupError {
+            kind,
+            err_num: 0,
+            inner: io::Error::new(io::ErrorKind::InvalidInput, msg.into()),
+            host: None,
+            service: None,
+            hints: None,
+        }
+    }
+
+    /// Record the query that caused this error.
+    pub(crate) fn with_query(
+        mut self,
+        host: Option<&[u8]>,
+        service: Option<&[u8]>,
+        hints: Option<AddrInfoHints>,
+    ) -> Self {
+        self.host = host.map(|h| String::from_utf8_lossy(h).into_owned());
+        self.service = service.map(|s| String::from_utf8_lossy(s).into_owned());
+        self.hints = hints;
+        self
+    }
+
     /// Get the error kind explicitly. If this is an
     /// io::Error, use From/Into to convert it.
     pub fn kind(&self) -> LookupErrorKind {
@@ -44,12 +105,125 @@ impl LookupError {
     pub fn error_num(&self) -> i32 {
         self.err_num
     }
+
+    /// The portable `GaiErrorCode` for this error, if the error number is
+    /// one known on this platform.
+    pub fn code(&self) -> Option<GaiErrorCode> {
+        GaiErrorCode::try_from(self.err_num).ok()
+    }
+
+    /// The OS error code for this error, if any. On unix, this is the
+    /// `errno` captured for `EAI_SYSTEM` errors. On windows, this is the
+    /// `WSAGetLastError` code.
+    pub fn raw_os_error(&self) -> Option<i32> {
+        // Windows errors wrap the OS error, to give it a better kind.
+        self.inner.raw_os_error().or_else(|| {
+            self.inner
+                .get_ref()?
+                .downcast_ref::<io::Error>()?
+                .raw_os_error()
+        })
+    }
+
+    /// Whether retrying the lookup might succeed. This is true for
+    /// transient kinds of error (see `LookupErrorKind::is_transient`), and
+    /// for errors that timed out.
+    pub fn is_transient(&self) -> bool {
+        self.kind.is_transient() || self.inner.kind() == io::ErrorKind::TimedOut
+    }
+
+    /// Recover the `LookupError` from an `io::Error` created by this
+    /// crate. Returns `None` if the error didn't come from a lookup.
+    pub fn from_io_error(err: &io::Error) -> Option<&LookupError> {
+        err.get_ref()?.downcast_ref()
+    }
+
+    /// The host that was looked up, if any. For `getnameinfo`, this is the
+    /// IP address. Names that aren't valid UTF8 are converted lossily.
+    pub fn host(&self) -> Option<&str> {
+        self.host.as_deref()
+    }
+
+    /// The service that was looked up, if any. For `getnameinfo`, this is
+    /// the port number.
+    pub fn service(&self) -> Option<&str> {
+        self.service.as_deref()
+    }
+
+    /// The hints passed to `getaddrinfo`, if any.
+    pub fn hints(&self) -> Option<AddrInfoHints> {
+        self.hints
+    }
+}
+
+impl fmt::Display for LookupError {
+    /// Describe the failed query. The cause, such as the `gai_strerror`
+    /// message, is the `source` of this error.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Lookup failed")?;
+        let mut query = Vec::new();
+        if let Some(host) = &self.host {
+            query.push(format!("host {host:?}"));
+        }
+        if let Some(service) = &self.service {
+            query.push(format!("service {service:?}"));
+        }
+        // Only hints that differ from the defaults are shown.
+        if let Some(hints) = self.hints {
+            if hints.flags != 0 {
+                query.push(format!("flags {:#x}", hints.flags));
+            }
+            if hints.address != 0 {
+                query.push(format!("family {}", hints.address));
+            }
+            if hints.socktype != 0 {
+                query.push(format!("socktype {}", hints.socktype));
+            }
+            if hints.protocol != 0 {
+                query.push(format!("protocol {}", hints.protocol));
+            }
+        }
+        if !query.is_empty() {
+            write!(f, " ({})", query.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for LookupError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.inner)
+    }
+}
+
+impl Clone for LookupError {
+    /// Clone the error. The inner `io::Error` can't be cloned directly,
+    /// so it's rebuilt from its OS error code, or its kind and message.
+    fn clone(&self) -> Self {
+        let inner = match (self.inner.raw_os_error(), self.raw_os_error()) {
+            (Some(code), _) => io::Error::from_raw_os_error(code),
+            (None, Some(code)) => {
+                io::Error::new(self.inner.kind(), io::Error::from_raw_os_error(code))
+            }
+            (None, None) => io::Error::new(self.inner.kind(), self.inner.to_string()),
+        };
+        LookupError {
+            kind: self.kind,
+            err_num: self.err_num,
+            inner,
+            host: self.host.clone(),
+            service: self.service.clone(),
+            hints: self.hints,
+        }
+    }
 }
 
 /// Different kinds of lookup errors that `getaddrinfo` and
 /// `getnameinfo` can return. These can be a little inconsitant
 /// between platforms, so it's recommended not to rely on them.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub enum LookupErrorKind {
     /// Temporary failure in name resolution.
     ///
@@ -78,8 +252,24 @@ pub enum LookupErrorKind {
     Service,
     /// Memory allocation failure.
     Memory,
+    /// The hostname was rejected by UTS #46 (IDNA) processing.
+    ///
+    /// Only returned when the `idna` feature is enabled.
+    Idna,
     /// System error returned in `errno'.
     System,
+    /// The host has no addresses in the requested address family.
+    ///
+    /// Returned by glibc, android and apple platforms.
+    AddrFamily,
+    /// An asynchronous lookup is still in progress (glibc only).
+    InProgress,
+    /// An asynchronous lookup was canceled (glibc only).
+    Canceled,
+    /// A result buffer passed to `getnameinfo` was too small.
+    Overflow,
+    /// The hostname couldn't be IDN encoded by glibc's `AI_IDN` support.
+    IdnEncode,
     /// An unknown result code was returned.
     ///
     /// For some platforms, you may wish to match on an unknown value directly.
@@ -94,64 +284,226 @@ pub enum LookupErrorKind {
 }
 
 impl LookupErrorKind {
-    #[cfg(all(not(windows), not(unix)))]
     /// Create a `LookupErrorKind` from a `gai` error.
     pub fn new(err: i32) -> Self {
-        LookupErrorKind::IO
+        GaiErrorCode::try_from(err).map_or(LookupErrorKind::IO, GaiErrorCode::kind)
     }
+}
 
-    #[cfg(unix)]
-    /// Create a `LookupErrorKind` from a `gai` error.
-    pub fn new(err: i32) -> Self {
-        use libc as c;
-        match err {
-            c::EAI_AGAIN => LookupErrorKind::Again,
-            c::EAI_BADFLAGS => LookupErrorKind::Badflags,
-            c::EAI_FAIL => LookupErrorKind::Fail,
-            c::EAI_FAMILY => LookupErrorKind::Family,
-            c::EAI_MEMORY => LookupErrorKind::Memory,
-            c::EAI_NONAME => LookupErrorKind::NoName,
-            // FreeBSD has no EAI_NODATA, so don't match it on that platform.
-            #[cfg(not(any(target_os = "freebsd", target_os = "emscripten")))]
-            c::EAI_NODATA => LookupErrorKind::NoData,
-            c::EAI_SERVICE => LookupErrorKind::Service,
-            c::EAI_SOCKTYPE => LookupErrorKind::Socktype,
-            c::EAI_SYSTEM => LookupErrorKind::System,
-            _ => LookupErrorKind::IO,
+/// Portable names for the `EAI_*` error codes returned by `getaddrinfo`
+/// and `getnameinfo`, which have different values on each platform.
+///
+/// Not every code exists on every platform. The glibc extensions
+/// (`AddrFamily`, `InProgress`, `Canceled` and `IdnEncode`) are only
+/// defined on linux with glibc, and some on android and apple platforms.
+///
+/// ```rust
+///   use dns_lookup::GaiErrorCode;
+///
+///   let raw = GaiErrorCode::NoName.to_raw().unwrap();
+///   assert_eq!(GaiErrorCode::try_from(raw).unwrap(), GaiErrorCode::NoName);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum GaiErrorCode {
+    /// `EAI_AGAIN`, or `WSATRY_AGAIN` on windows.
+    Again,
+    /// `EAI_BADFLAGS`, or `WSAEINVAL` on windows.
+    Badflags,
+    /// `EAI_FAIL`, or `WSANO_RECOVERY` on windows.
+    Fail,
+    /// `EAI_FAMILY`, or `WSAEAFNOSUPPORT` on windows.
+    Family,
+    /// `EAI_MEMORY`, or `WSA_NOT_ENOUGH_MEMORY` on windows.
+    Memory,
+    /// `EAI_NONAME`, or `WSAHOST_NOT_FOUND` on windows.
+    NoName,
+    /// `EAI_NODATA`, or `WSANO_DATA` on windows.
+    NoData,
+    /// `EAI_SERVICE`, or `WSATYPE_NOT_FOUND` on windows.
+    Service,
+    /// `EAI_SOCKTYPE`, or `WSAESOCKTNOSUPPORT` on windows.
+    Socktype,
+    /// `EAI_SYSTEM`.
+    System,
+    /// `EAI_ADDRFAMILY`.
+    AddrFamily,
+    /// `EAI_INPROGRESS`.
+    InProgress,
+    /// `EAI_CANCELED`.
+    Canceled,
+    /// `EAI_OVERFLOW`.
+    Overflow,
+    /// `EAI_IDN_ENCODE`.
+    IdnEncode,
+}
+
+impl GaiErrorCode {
+    /// The raw value of this code on the current platform, or `None` if
+    /// the platform doesn't define it.
+    pub fn to_raw(self) -> Option<i32> {
+        raw_codes()
+            .iter()
+            .find(|(code, _)| *code == self)
+            .map(|(_, raw)| *raw)
+    }
+
+    /// The `LookupErrorKind` for this code.
+    pub fn kind(self) -> LookupErrorKind {
+        match self {
+            GaiErrorCode::Again => LookupErrorKind::Again,
+            GaiErrorCode::Badflags => LookupErrorKind::Badflags,
+            GaiErrorCode::Fail => LookupErrorKind::Fail,
+            GaiErrorCode::Family => LookupErrorKind::Family,
+            GaiErrorCode::Memory => LookupErrorKind::Memory,
+            GaiErrorCode::NoName => LookupErrorKind::NoName,
+            GaiErrorCode::NoData => LookupErrorKind::NoData,
+            GaiErrorCode::Service => LookupErrorKind::Service,
+            GaiErrorCode::Socktype => LookupErrorKind::Socktype,
+            GaiErrorCode::System => LookupErrorKind::System,
+            GaiErrorCode::AddrFamily => LookupErrorKind::AddrFamily,
+            GaiErrorCode::InProgress => LookupErrorKind::InProgress,
+            GaiErrorCode::Canceled => LookupErrorKind::Canceled,
+            GaiErrorCode::Overflow => LookupErrorKind::Overflow,
+            GaiErrorCode::IdnEncode => LookupErrorKind::IdnEncode,
         }
     }
+}
 
-    #[cfg(windows)]
-    /// Create a `LookupErrorKind` from a `gai` error.
-    pub fn new(err: i32) -> Self {
-        use windows_sys::Win32::Networking::WinSock;
-        match err {
-            WinSock::WSATRY_AGAIN => LookupErrorKind::Again,
-            WinSock::WSAEINVAL => LookupErrorKind::Badflags,
-            WinSock::WSANO_RECOVERY => LookupErrorKind::Fail,
-            WinSock::WSAEAFNOSUPPORT => LookupErrorKind::Family,
-            WinSock::WSA_NOT_ENOUGH_MEMORY => LookupErrorKind::Memory,
-            WinSock::WSAHOST_NOT_FOUND => LookupErrorKind::NoName,
-            WinSock::WSANO_DATA => LookupErrorKind::NoData,
-            WinSock::WSATYPE_NOT_FOUND => LookupErrorKind::Service,
-            WinSock::WSAESOCKTNOSUPPORT => LookupErrorKind::Socktype,
-            _ => LookupErrorKind::IO,
+impl TryFrom<i32> for GaiErrorCode {
+    type Error = io::Error;
+
+    /// Convert a raw error code for the current platform, returning an
+    /// error for values without a matching variant.
+    fn try_from(value: i32) -> io::Result<GaiErrorCode> {
+        raw_codes()
+            .iter()
+            .find(|(_, raw)| *raw == value)
+            .map(|(code, _)| *code)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown gai error code: {value}"),
+                )
+            })
+    }
+}
+
+// Raw values of each GaiErrorCode on this platform.
+#[cfg(all(not(windows), not(unix)))]
+fn raw_codes() -> &'static [(GaiErrorCode, i32)] {
+    &[]
+}
+
+#[cfg(unix)]
+fn raw_codes() -> &'static [(GaiErrorCode, i32)] {
+    use libc as c;
+    &[
+        (GaiErrorCode::Again, c::EAI_AGAIN),
+        (GaiErrorCode::Badflags, c::EAI_BADFLAGS),
+        (GaiErrorCode::Fail, c::EAI_FAIL),
+        (GaiErrorCode::Family, c::EAI_FAMILY),
+        (GaiErrorCode::Memory, c::EAI_MEMORY),
+        (GaiErrorCode::NoName, c::EAI_NONAME),
+        // FreeBSD has no EAI_NODATA, so don't match it on that platform.
+        #[cfg(not(any(target_os = "freebsd", target_os = "emscripten")))]
+        (GaiErrorCode::NoData, c::EAI_NODATA),
+        (GaiErrorCode::Service, c::EAI_SERVICE),
+        (GaiErrorCode::Socktype, c::EAI_SOCKTYPE),
+        (GaiErrorCode::System, c::EAI_SYSTEM),
+        (GaiErrorCode::Overflow, c::EAI_OVERFLOW),
+        // libc doesn't define the glibc extensions, so use the values from
+        // glibc's netdb.h.
+        #[cfg(all(target_os = "linux", target_env = "gnu"))]
+        (GaiErrorCode::AddrFamily, -9),
+        #[cfg(all(target_os = "linux", target_env = "gnu"))]
+        (GaiErrorCode::InProgress, -100),
+        #[cfg(all(target_os = "linux", target_env = "gnu"))]
+        (GaiErrorCode::Canceled, -101),
+        #[cfg(all(target_os = "linux", target_env = "gnu"))]
+        (GaiErrorCode::IdnEncode, -105),
+        #[cfg(any(target_os = "android", target_vendor = "apple"))]
+        (GaiErrorCode::AddrFamily, 1),
+    ]
+}
+
+#[cfg(windows)]
+fn raw_codes() -> &'static [(GaiErrorCode, i32)] {
+    use windows_sys::Win32::Networking::WinSock;
+    &[
+        (GaiErrorCode::Again, WinSock::WSATRY_AGAIN),
+        (GaiErrorCode::Badflags, WinSock::WSAEINVAL),
+        (GaiErrorCode::Fail, WinSock::WSANO_RECOVERY),
+        (GaiErrorCode::Family, WinSock::WSAEAFNOSUPPORT),
+        (GaiErrorCode::Memory, WinSock::WSA_NOT_ENOUGH_MEMORY),
+        (GaiErrorCode::NoName, WinSock::WSAHOST_NOT_FOUND),
+        (GaiErrorCode::NoData, WinSock::WSANO_DATA),
+        (GaiErrorCode::Service, WinSock::WSATYPE_NOT_FOUND),
+        (GaiErrorCode::Socktype, WinSock::WSAESOCKTNOSUPPORT),
+    ]
+}
+
+impl LookupErrorKind {
+    /// Whether this kind of error is temporary, so retrying the lookup
+    /// might succeed. Only `Again` and `System` errors are transient.
+    pub fn is_transient(self) -> bool {
+        matches!(self, LookupErrorKind::Again | LookupErrorKind::System)
+    }
+
+    /// Recover the kind of lookup error from an `io::Error` created by this
+    /// crate. Returns `None` if the error didn't come from a lookup.
+    pub fn from_io_error(err: &io::Error) -> Option<Self> {
+        LookupError::from_io_error(err).map(LookupError::kind)
+    }
+
+    // The `io::ErrorKind` that best describes this kind of error.
+    fn io_kind(self) -> io::ErrorKind {
+        match self {
+            LookupErrorKind::NoName | LookupErrorKind::NoData | LookupErrorKind::AddrFamily => {
+                io::ErrorKind::NotFound
+            }
+            LookupErrorKind::Again => io::ErrorKind::TimedOut,
+            LookupErrorKind::InProgress => io::ErrorKind::WouldBlock,
+            LookupErrorKind::Canceled => io::ErrorKind::Interrupted,
+            LookupErrorKind::Memory => io::ErrorKind::OutOfMemory,
+            LookupErrorKind::Family | LookupErrorKind::Socktype => io::ErrorKind::Unsupported,
+            LookupErrorKind::Badflags
+            | LookupErrorKind::Service
+            | LookupErrorKind::Idna
+            | LookupErrorKind::IdnEncode => io::ErrorKind::InvalidInput,
+            LookupErrorKind::Fail
+            | LookupErrorKind::Overflow
+            | LookupErrorKind::System
+            | LookupErrorKind::Unknown
+            | LookupErrorKind::IO => io::ErrorKind::Other,
         }
     }
 }
 
 impl From<LookupError> for io::Error {
+    /// Convert to an `io::Error` wrapping the `LookupError`, which can be
+    /// recovered with `LookupError::from_io_error`. Any OS error code, such
+    /// as the `errno` of an `EAI_SYSTEM` error, is available from the
+    /// recovered error's `raw_os_error`.
     fn from(err: LookupError) -> io::Error {
-        err.inner
+        io::Error::new(err.inner.kind(), err)
     }
 }
 
 impl From<io::Error> for LookupError {
     fn from(err: io::Error) -> LookupError {
+        // Unwrap a LookupError that was converted to an io::Error.
+        let err = match err.downcast::<LookupError>() {
+            Ok(err) => return err,
+            Err(err) => err,
+        };
         LookupError {
             kind: LookupErrorKind::IO,
             err_num: 0,
             inner: err,
+            host: None,
+            service: None,
+            hints: None,
         }
     }
 }
@@ -167,10 +519,13 @@ impl From<ffi::NulError> for LookupError {
 /// Given a gai error, return an `std::io::Error` with
 /// the appropriate error message. Note `0` is not an
 /// error, but will still map to an error
-pub(crate) fn gai_err_to_io_err(err: i32) -> io::Error {
+pub(crate) fn gai_err_to_io_err(err: i32, _errno: i32) -> io::Error {
     match (err) {
-        0 => io::Error::new(io::ErrorKind::Other, "address information lookup success"),
-        _ => io::Error::new(io::ErrorKind::Other, "failed to lookup address information"),
+        0 => io::Error::other("address information lookup success"),
+        _ => io::Error::new(
+            LookupErrorKind::new(err).io_kind(),
+            "failed to lookup address information",
+        ),
     }
 }
 
@@ -178,12 +533,12 @@ pub(crate) fn gai_err_to_io_err(err: i32) -> io::Error {
 /// Given a gai error, return an `std::io::Error` with
 /// the appropriate error message. Note `0` is not an
 /// error, but will still map to an error
-pub(crate) fn gai_err_to_io_err(err: i32) -> io::Error {
+pub(crate) fn gai_err_to_io_err(err: i32, errno: i32) -> io::Error {
     use libc::{gai_strerror, EAI_SYSTEM};
 
     match err {
         0 => return io::Error::other("address information lookup success"),
-        EAI_SYSTEM => return io::Error::last_os_error(),
+        EAI_SYSTEM => return io::Error::from_raw_os_error(errno),
         _ => {}
     }
 
@@ -192,17 +547,145 @@ pub(crate) fn gai_err_to_io_err(err: i32) -> io::Error {
             .unwrap()
             .to_owned()
     };
-    io::Error::other(&format!("failed to lookup address information: {detail}")[..])
+    io::Error::new(
+        LookupErrorKind::new(err).io_kind(),
+        format!("failed to lookup address information: {detail}"),
+    )
 }
 
 #[cfg(windows)]
 /// Given a gai error, return an `std::io::Error` with
 /// the appropriate error message. Note `0` is not an
 /// error, but will still map to an error
-pub(crate) fn gai_err_to_io_err(err: i32) -> io::Error {
-    use windows_sys::Win32::Networking::WinSock::WSAGetLastError;
+pub(crate) fn gai_err_to_io_err(err: i32, errno: i32) -> io::Error {
     match err {
-        0 => io::Error::new(io::ErrorKind::Other, "address information lookup success"),
-        _ => io::Error::from_raw_os_error(unsafe { WSAGetLastError() }),
+        0 => io::Error::other("address information lookup success"),
+        // Keep the WSA error, but with the same kind as on unix.
+        _ => io::Error::new(
+            LookupErrorKind::new(err).io_kind(),
+            io::Error::from_raw_os_error(errno),
+        ),
+    }
+}
+
+/// Read `errno`, or `WSAGetLastError` on windows. This must be called
+/// right after the failing call, before anything can overwrite it.
+pub(crate) fn last_errno() -> i32 {
+    io::Error::last_os_error().raw_os_error().unwrap_or(0)
+}
+
+#[test]
+fn test_lookup_error_context() {
+    use crate::addrinfo::getaddrinfo;
+
+    // An unknown address family fails without a DNS lookup.
+    let hints = AddrInfoHints {
+        address: 12345,
+        ..AddrInfoHints::default()
+    };
+    let err = getaddrinfo(Some("not-an-ip"), Some("80"), Some(hints))
+        .err()
+        .unwrap();
+    assert_eq!(err.host(), Some("not-an-ip"));
+    assert_eq!(err.service(), Some("80"));
+    assert_eq!(err.hints(), Some(hints));
+    assert_eq!(
+        err.to_string(),
+        r#"Lookup failed (host "not-an-ip", service "80", family 12345)"#
+    );
+    // The cause is only given by the source, not repeated in the message.
+    let source = err.source().unwrap().to_string();
+    assert!(!err.to_string().contains(&source));
+
+    let boxed: Box<dyn Error> = Box::new(err.clone());
+    assert_eq!(boxed.to_string(), err.to_string());
+}
+
+#[test]
+fn test_lookup_error_io_kind() {
+    let err = LookupError::invalid(LookupErrorKind::Badflags, "bad flags");
+    let io_err: io::Error = err.into();
+    assert_eq!(io_err.kind(), io::ErrorKind::InvalidInput);
+    assert!(matches!(
+        LookupErrorKind::from_io_error(&io_err),
+        Some(LookupErrorKind::Badflags)
+    ));
+    let err: LookupError = io_err.into();
+    assert!(matches!(err.kind(), LookupErrorKind::Badflags));
+
+    assert!(LookupErrorKind::from_io_error(&io::Error::other("other")).is_none());
+
+    #[cfg(unix)]
+    {
+        let io_err: io::Error = LookupError::new(libc::EAI_NONAME).into();
+        assert_eq!(io_err.kind(), io::ErrorKind::NotFound);
+        let io_err: io::Error = LookupError::new(libc::EAI_AGAIN).into();
+        assert_eq!(io_err.kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::Networking::WinSock;
+
+        let err =
+            LookupError::from_gai_and_errno(WinSock::WSAHOST_NOT_FOUND, WinSock::WSAHOST_NOT_FOUND);
+        assert_eq!(err.raw_os_error(), Some(WinSock::WSAHOST_NOT_FOUND));
+        assert_eq!(err.clone().raw_os_error(), err.raw_os_error());
+        let io_err: io::Error = err.into();
+        assert_eq!(io_err.kind(), io::ErrorKind::NotFound);
+        let io_err: io::Error = LookupError::new(WinSock::WSATRY_AGAIN).into();
+        assert_eq!(io_err.kind(), io::ErrorKind::TimedOut);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_lookup_error_errno() {
+    let err = LookupError::from_gai_and_errno(libc::EAI_SYSTEM, libc::ENOENT);
+    assert!(matches!(err.kind(), LookupErrorKind::System));
+    assert_eq!(err.raw_os_error(), Some(libc::ENOENT));
+    assert_eq!(err.clone().raw_os_error(), Some(libc::ENOENT));
+    let io_err: io::Error = err.into();
+    assert_eq!(io_err.kind(), io::ErrorKind::NotFound);
+
+    // The error and its query survive a round trip through io::Error.
+    let err = LookupError::from_gai_and_errno(libc::EAI_SYSTEM, libc::ENOENT).with_query(
+        Some(b"example.com"),
+        None,
+        None,
+    );
+    let io_err: io::Error = err.into();
+    assert_eq!(
+        LookupErrorKind::from_io_error(&io_err),
+        Some(LookupErrorKind::System)
+    );
+    let err = LookupError::from_io_error(&io_err).unwrap();
+    assert_eq!(err.raw_os_error(), Some(libc::ENOENT));
+    assert_eq!(err.host(), Some("example.com"));
+
+    let err = LookupError::from_gai_and_errno(libc::EAI_NONAME, libc::ENOENT);
+    assert_eq!(err.raw_os_error(), None);
+}
+
+#[test]
+fn test_gai_error_code() {
+    for code in [
+        GaiErrorCode::Again,
+        GaiErrorCode::NoName,
+        GaiErrorCode::Fail,
+    ] {
+        let raw = code.to_raw().unwrap();
+        assert_eq!(GaiErrorCode::try_from(raw).unwrap(), code);
+        let err = LookupError::new(raw);
+        assert_eq!(err.code(), Some(code));
+        assert_eq!(err.kind(), code.kind());
+    }
+    assert!(GaiErrorCode::try_from(0).is_err());
+    assert_eq!(LookupError::new(0).code(), None);
+
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    {
+        assert_eq!(GaiErrorCode::IdnEncode.to_raw(), Some(-105));
+        assert_eq!(LookupErrorKind::new(-9), LookupErrorKind::AddrFamily);
     }
 }
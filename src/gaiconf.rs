@@ -0,0 +1,304 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+
+use crate::sort::{prefix_matches, PolicyEntry, PolicyTable, ScopeEntry};
+
+/// Settings parsed from a glibc `gai.conf` file.
+///
+/// Supports the `label`, `precedence`, `scopev4` and `reload` directives.
+/// As in glibc, any `label` or `precedence` lines replace the whole default
+/// label or precedence table respectively.
+///
+/// ```rust
+///   use dns_lookup::{AddressSorter, GaiConf};
+///
+///   let conf = GaiConf::parse("precedence ::ffff:0:0/96 100\n").unwrap();
+///   let sorter = AddressSorter::with_policy(conf.into_policy_table());
+///   let _ = sorter;
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GaiConf {
+    policy: PolicyTable,
+    reload: bool,
+}
+
+impl GaiConf {
+    /// Default location of gai.conf.
+    pub const PATH: &'static str = "/etc/gai.conf";
+
+    /// Parse the contents of a gai.conf file.
+    pub fn parse(conf: &str) -> Result<Self, GaiConfError> {
+        let mut labels = Vec::new();
+        let mut precedences = Vec::new();
+        let mut scopev4 = Vec::new();
+        let mut reload = false;
+
+        for (idx, line) in conf.lines().enumerate() {
+            let err = |msg: String| GaiConfError { line: idx + 1, msg };
+            let line = line.split('#').next().unwrap_or_default();
+            let mut words = line.split_whitespace();
+            let Some(directive) = words.next() else {
+                continue;
+            };
+            let args = words.collect::<Vec<_>>();
+            let arity = match directive {
+                "label" | "precedence" | "scopev4" => 2,
+                "reload" => 1,
+                _ => return Err(err(format!("Unknown directive '{directive}'"))),
+            };
+            if args.len() != arity {
+                return Err(err(format!(
+                    "Expected {arity} argument(s) to '{directive}', found {}",
+                    args.len()
+                )));
+            }
+
+            match directive {
+                "label" | "precedence" => {
+                    let (prefix, prefix_len) = parse_ipv6_mask(args[0]).map_err(err)?;
+                    let value = args[1]
+                        .parse::<u32>()
+                        .map_err(|_| err(format!("Invalid {directive} value '{}'", args[1])))?;
+                    let table = match directive {
+                        "label" => &mut labels,
+                        _ => &mut precedences,
+                    };
+                    table.push((prefix, prefix_len, value));
+                }
+                "scopev4" => {
+                    let (prefix, prefix_len) = parse_ipv4_mask(args[0]).map_err(err)?;
+                    let scope = args[1]
+                        .parse::<u8>()
+                        .ok()
+                        .filter(|s| *s <= 0xf)
+                        .ok_or_else(|| err(format!("Invalid scope value '{}'", args[1])))?;
+                    scopev4.push(ScopeEntry {
+                        prefix,
+                        prefix_len,
+                        scope,
+                    });
+                }
+                _ => {
+                    reload = match args[0] {
+                        "yes" => true,
+                        "no" => false,
+                        other => return Err(err(format!("Invalid reload value '{other}'"))),
+                    };
+                }
+            }
+        }
+
+        let default = PolicyTable::default();
+        if labels.is_empty() {
+            labels = default
+                .entries()
+                .iter()
+                .map(|e| (e.prefix, e.prefix_len, e.label))
+                .collect();
+        }
+        if precedences.is_empty() {
+            precedences = default
+                .entries()
+                .iter()
+                .map(|e| (e.prefix, e.prefix_len, e.precedence))
+                .collect();
+        }
+
+        Ok(GaiConf {
+            policy: merge(&labels, &precedences).with_scopev4(scopev4),
+            reload,
+        })
+    }
+
+    /// Read and parse a gai.conf file. Parse errors are returned as
+    /// `io::ErrorKind::InvalidData`, containing a `GaiConfError`.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let conf = fs::read_to_string(path)?;
+        GaiConf::parse(&conf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// The policy table described by this file.
+    pub fn policy_table(&self) -> &PolicyTable {
+        &self.policy
+    }
+
+    /// Convert into the policy table described by this file, to be used
+    /// with `AddressSorter`.
+    pub fn into_policy_table(self) -> PolicyTable {
+        self.policy
+    }
+
+    /// Whether the file asked for `reload yes`, to be re-read when it
+    /// changes. Watching the file is left to the caller.
+    pub fn reload(&self) -> bool {
+        self.reload
+    }
+}
+
+/// An error found while parsing a gai.conf file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GaiConfError {
+    line: usize,
+    msg: String,
+}
+
+impl GaiConfError {
+    /// Line number the error was found on, starting from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl fmt::Display for GaiConfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "gai.conf line {}: {}", self.line, self.msg)
+    }
+}
+
+impl Error for GaiConfError {}
+
+// Parse an IPv6 prefix, like `::ffff:0:0/96`. Without a length the prefix
+// covers a single address.
+fn parse_ipv6_mask(mask: &str) -> Result<(Ipv6Addr, u8), String> {
+    let (addr, len) = split_mask(mask, 128)?;
+    let addr = addr
+        .parse()
+        .map_err(|_| format!("Invalid IPv6 address '{addr}'"))?;
+    Ok((addr, len))
+}
+
+// Parse an IPv4 prefix for scopev4. Like glibc, this accepts either an IPv4
+// prefix or an IPv4-mapped IPv6 prefix, like `::ffff:169.254.0.0/112`.
+fn parse_ipv4_mask(mask: &str) -> Result<(Ipv4Addr, u8), String> {
+    let invalid = || format!("Invalid IPv4 prefix '{mask}'");
+    match mask.split('/').next().unwrap_or_default().parse() {
+        Ok(IpAddr::V4(addr)) => Ok((addr, split_mask(mask, 32)?.1)),
+        Ok(IpAddr::V6(addr)) => {
+            let (_, len) = split_mask(mask, 128)?;
+            let v4 = addr.to_ipv4_mapped().ok_or_else(invalid)?;
+            let len = len.checked_sub(96).ok_or_else(invalid)?;
+            Ok((v4, len))
+        }
+        Err(_) => Err(invalid()),
+    }
+}
+
+fn split_mask(mask: &str, max_len: u8) -> Result<(&str, u8), String> {
+    match mask.split_once('/') {
+        None => Ok((mask, max_len)),
+        Some((addr, len)) => match len.parse() {
+            Ok(len) if len <= max_len => Ok((addr, len)),
+            _ => Err(format!("Invalid prefix length '{len}'")),
+        },
+    }
+}
+
+// Combine separate label and precedence tables into a single policy table.
+// Every prefix from either table becomes an entry, taking its label and
+// precedence from the longest prefix containing it in each table, so
+// lookups give the same result as searching each table separately.
+fn merge(labels: &[(Ipv6Addr, u8, u32)], precedences: &[(Ipv6Addr, u8, u32)]) -> PolicyTable {
+    let find = |table: &[(Ipv6Addr, u8, u32)], prefix: Ipv6Addr, len: u8| {
+        table
+            .iter()
+            .filter(|(p, l, _)| *l <= len && prefix_matches(*p, *l, u128::from(prefix)))
+            .max_by_key(|(_, l, _)| *l)
+            .map_or(0, |(_, _, v)| *v)
+    };
+
+    let mut entries: Vec<PolicyEntry> = Vec::new();
+    for (prefix, prefix_len, _) in labels.iter().chain(precedences) {
+        let mask = u128::MAX
+            .checked_shl(128 - u32::from(*prefix_len))
+            .unwrap_or(0);
+        let prefix = Ipv6Addr::from(u128::from(*prefix) & mask);
+        if entries
+            .iter()
+            .any(|e| e.prefix == prefix && e.prefix_len == *prefix_len)
+        {
+            continue;
+        }
+        entries.push(PolicyEntry {
+            prefix,
+            prefix_len: *prefix_len,
+            precedence: find(precedences, prefix, *prefix_len),
+            label: find(labels, prefix, *prefix_len),
+        });
+    }
+    PolicyTable::new(entries)
+}
+
+#[test]
+fn test_gai_conf() {
+    let conf = GaiConf::parse(
+        "# Prefer IPv4\n\
+         \n\
+         reload yes\n\
+         precedence ::1/128 50\n\
+         precedence ::/0 40\n\
+         precedence ::ffff:0:0/96 100  # IPv4\n\
+         scopev4 ::ffff:169.254.0.0/112 2\n\
+         scopev4 10.0.0.0/8 5\n",
+    )
+    .unwrap();
+    assert!(conf.reload());
+    assert_eq!(
+        conf.policy_table().scopev4(),
+        &[
+            ScopeEntry {
+                prefix: Ipv4Addr::new(169, 254, 0, 0),
+                prefix_len: 16,
+                scope: 2,
+            },
+            ScopeEntry {
+                prefix: Ipv4Addr::new(10, 0, 0, 0),
+                prefix_len: 8,
+                scope: 5,
+            },
+        ]
+    );
+
+    // Precedences are replaced, while labels keep their defaults.
+    let default = PolicyTable::default();
+    let table = conf.into_policy_table();
+    let entry = |addr: &str| {
+        let addr: Ipv6Addr = addr.parse().unwrap();
+        let find = |t: &PolicyTable| {
+            *t.entries()
+                .iter()
+                .filter(|e| prefix_matches(e.prefix, e.prefix_len, u128::from(addr)))
+                .max_by_key(|e| e.prefix_len)
+                .unwrap()
+        };
+        (find(&table), find(&default))
+    };
+    let (v4, default_v4) = entry("::ffff:10.0.0.1");
+    assert_eq!((v4.precedence, v4.label), (100, default_v4.label));
+    let (teredo, default_teredo) = entry("2001:0:1::1");
+    assert_eq!(
+        (teredo.precedence, teredo.label),
+        (40, default_teredo.label)
+    );
+}
+
+#[test]
+fn test_gai_conf_errors() {
+    let err = GaiConf::parse("label ::1/128 0\nprecedence ::1/129 50\n").unwrap_err();
+    assert_eq!(err.line(), 2);
+
+    let err = GaiConf::parse("\n\nscopev4 ::1/128 2\n").unwrap_err();
+    assert_eq!(err.line(), 3);
+
+    let err = GaiConf::parse("reload maybe").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "gai.conf line 1: Invalid reload value 'maybe'"
+    );
+
+    assert!(GaiConf::parse("label ::1/128").is_err());
+    assert!(GaiConf::parse("sortv4 10.0.0.0/8 1").is_err());
+}
@@ -1,7 +1,6 @@
 use std::ffi::CStr;
 use std::io;
 use std::os::raw::c_char;
-use std::str;
 
 #[cfg(unix)]
 use libc::{c_char as libc_c_char, gethostname as c_gethostname};
@@ -13,7 +12,18 @@ type libc_c_char = u8;
 use windows_sys::Win32::Networking::WinSock::gethostname as c_gethostname;
 
 /// Fetch the local hostname.
+///
+/// Returns an error if the hostname isn't valid UTF8. Use
+/// `get_hostname_bytes` to fetch it without decoding.
 pub fn get_hostname() -> Result<String, io::Error> {
+    String::from_utf8(get_hostname_bytes()?).map_err(|_| io::Error::other("Non-UTF8 hostname"))
+}
+
+/// Fetch the local hostname as raw bytes, without decoding it as UTF8.
+///
+/// On unix, the result can be converted to an `OsString` using
+/// `std::os::unix::ffi::OsStringExt::from_vec`.
+pub fn get_hostname_bytes() -> Result<Vec<u8>, io::Error> {
     // Prime windows.
     #[cfg(windows)]
     crate::win::init_winsock();
@@ -29,9 +39,7 @@ pub fn get_hostname() -> Result<String, io::Error> {
 
     let hostname = unsafe { CStr::from_ptr(c_name.as_ptr() as *const c_char) };
 
-    str::from_utf8(hostname.to_bytes())
-        .map(|h| h.to_owned())
-        .map_err(|_| io::Error::other("Non-UTF8 hostname"))
+    Ok(hostname.to_bytes().to_vec())
 }
 
 #[test]
@@ -39,3 +47,9 @@ fn test_get_hostname() {
     // We don't know the hostname of the local box, so just verify it doesn't return an error.
     get_hostname().unwrap();
 }
+
+#[test]
+fn test_get_hostname_bytes() {
+    let hostname = get_hostname().unwrap();
+    assert_eq!(get_hostname_bytes().unwrap(), hostname.into_bytes());
+}
@@ -0,0 +1,77 @@
+use std::borrow::Cow;
+use std::net::IpAddr;
+
+use idna::AsciiDenyList;
+
+use crate::err::{LookupError, LookupErrorKind};
+
+// Convert a hostname to its ASCII form using UTS #46 processing, which maps
+// and normalizes it, and applies the bidi and contextual rules. IP address
+// literals are passed through unchanged. Domain names can't contain `:`, so
+// any host with one is treated as an IPv6 literal, which may have a
+// case-sensitive zone ID like `fe80::1%eth0`.
+pub(crate) fn to_ascii(host: &str) -> Result<Cow<'_, str>, LookupError> {
+    if host.contains(':') || host.parse::<IpAddr>().is_ok() {
+        return Ok(Cow::Borrowed(host));
+    }
+    idna::domain_to_ascii_cow(host.as_bytes(), AsciiDenyList::EMPTY).map_err(|_| {
+        LookupError::invalid(
+            LookupErrorKind::Idna,
+            format!("Hostname '{host}' was rejected by IDNA processing"),
+        )
+    })
+}
+
+// Decode any `xn--` labels in a hostname returned by getnameinfo. Names
+// without Punycode labels are returned unchanged.
+pub(crate) fn to_unicode(host: String) -> Result<String, LookupError> {
+    let has_punycode = host.split('.').any(|label| {
+        label
+            .get(..4)
+            .is_some_and(|p| p.eq_ignore_ascii_case("xn--"))
+    });
+    if !has_punycode {
+        return Ok(host);
+    }
+    match idna::domain_to_unicode(&host) {
+        (decoded, Ok(())) => Ok(decoded),
+        (_, Err(_)) => Err(LookupError::invalid(
+            LookupErrorKind::Idna,
+            format!("Hostname '{host}' was rejected by IDNA processing"),
+        )),
+    }
+}
+
+#[test]
+fn test_idna() {
+    assert_eq!(to_ascii("Bücher.example").unwrap(), "xn--bcher-kva.example");
+    assert_eq!(to_ascii("localhost").unwrap(), "localhost");
+    assert_eq!(to_ascii("::1").unwrap(), "::1");
+    assert_eq!(to_ascii("fe80::1%LO").unwrap(), "fe80::1%LO");
+    assert_eq!(
+        to_unicode("xn--bcher-kva.example".to_owned()).unwrap(),
+        "bücher.example"
+    );
+    assert_eq!(to_unicode("localhost".to_owned()).unwrap(), "localhost");
+
+    // Bidi rule: a right-to-left label can't start with a digit.
+    let err = to_ascii("1\u{05d0}.example").unwrap_err();
+    assert!(matches!(err.kind(), LookupErrorKind::Idna));
+    // Invalid Punycode.
+    let err = to_unicode("xn--a.example".to_owned()).unwrap_err();
+    assert!(matches!(err.kind(), LookupErrorKind::Idna));
+
+    let ips = crate::lookup_host("localhost").unwrap().collect::<Vec<_>>();
+    assert!(ips.contains(&"127.0.0.1".parse().unwrap()));
+
+    // Errors record the host as given, not its Punycode form. An unknown
+    // address family fails without a DNS lookup.
+    let hints = crate::AddrInfoHints {
+        address: 12345,
+        ..crate::AddrInfoHints::default()
+    };
+    let err = crate::getaddrinfo(Some("Bücher.example"), None, Some(hints))
+        .err()
+        .unwrap();
+    assert_eq!(err.host(), Some("Bücher.example"));
+}
@@ -75,10 +75,21 @@
 //! ```
 
 mod addrinfo;
+mod bulk;
+mod connect;
+mod domain;
 mod err;
+mod gaiconf;
 mod hostname;
+#[cfg(feature = "idna")]
+mod idn;
 mod lookup;
 mod nameinfo;
+mod retry;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod singleflight;
+mod sort;
 mod types;
 
 #[cfg(unix)]
@@ -87,9 +98,20 @@ extern crate libc;
 #[cfg(windows)]
 mod win;
 
-pub use addrinfo::{getaddrinfo, AddrInfo, AddrInfoHints, AddrInfoIter};
-pub use err::{LookupError, LookupErrorKind};
-pub use hostname::get_hostname;
-pub use lookup::{lookup_addr, lookup_host};
-pub use nameinfo::getnameinfo;
-pub use types::{AddrFamily, Protocol, SockType};
+pub use addrinfo::{
+    getaddrinfo, getaddrinfo_bytes, AddrInfo, AddrInfoHints, AddrInfoHintsBuilder, AddrInfoIter,
+    GroupedAddrInfo, RawAddrInfo, RawAddrInfoIter,
+};
+pub use bulk::{resolve_many, Completions, Progress, ResolveMany, ResolveResult};
+pub use connect::{connect_tcp, ConnectError, Connection, FailedAttempt, HappyEyeballs};
+pub use domain::{DomainMode, DomainName, DomainNameError};
+pub use err::{GaiErrorCode, LookupError, LookupErrorKind};
+pub use gaiconf::{GaiConf, GaiConfError};
+pub use hostname::{get_hostname, get_hostname_bytes};
+pub use lookup::{lookup_addr, lookup_host, lookup_host_with, FamilyPolicy, LookupOptions};
+pub use nameinfo::{getnameinfo, getnameinfo_bytes};
+pub use retry::{retry_lookup, RetryingResolver};
+pub use singleflight::{getaddrinfo_coalesced, lookup_host_coalesced};
+pub use socket2::SockAddr;
+pub use sort::{AddressSorter, PolicyEntry, PolicyTable, ScopeEntry};
+pub use types::{AddrFamily, AddrInfoFlags, NameInfoFlags, Protocol, SockType};
@@ -1,36 +1,118 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashSet;
+use std::hash::{BuildHasher, Hasher};
 use std::io;
 use std::net::IpAddr;
 use std::str;
 
 #[cfg(unix)]
-use libc::{NI_NAMEREQD, NI_NUMERICSERV, SOCK_STREAM};
+use libc::SOCK_STREAM;
 
 #[cfg(windows)]
-use windows_sys::Win32::Networking::WinSock::{NI_NAMEREQD, NI_NUMERICSERV, SOCK_STREAM};
+use windows_sys::Win32::Networking::WinSock::SOCK_STREAM;
 
 use crate::addrinfo::{getaddrinfo, AddrInfoHints};
 use crate::nameinfo::getnameinfo;
+use crate::types::{AddrFamily, NameInfoFlags};
+
+/// Which address families `lookup_host_with` returns, and in what order.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FamilyPolicy {
+    /// Return addresses of any family, in the order returned by libc.
+    #[default]
+    Any,
+    /// Return IPv4 addresses before IPv6 addresses.
+    PreferIpv4,
+    /// Return IPv6 addresses before IPv4 addresses.
+    PreferIpv6,
+    /// Only return IPv4 addresses.
+    Ipv4Only,
+    /// Only return IPv6 addresses.
+    Ipv6Only,
+    /// Alternate between address families, starting with the family of the
+    /// first address returned by libc.
+    Interleave,
+}
+
+/// Options for `lookup_host_with`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LookupOptions {
+    /// Which address families to return, and in what order.
+    pub family: FamilyPolicy,
+    /// Shuffle addresses before applying the family policy, for client-side
+    /// load balancing. Addresses stay grouped by family if the policy
+    /// prefers one.
+    pub shuffle: bool,
+    /// Remove repeated addresses, keeping the first occurrence.
+    pub dedup: bool,
+}
 
 /// Lookup the address for a given hostname via DNS.
 ///
 /// Returns an iterator of IP Addresses, or an `io::Error` on failure.
 pub fn lookup_host(host: &str) -> io::Result<impl Iterator<Item = IpAddr> + use<>> {
-    #[allow(clippy::unnecessary_cast)]
-    let hints = AddrInfoHints {
-        socktype: SOCK_STREAM as i32,
-        ..AddrInfoHints::default()
-    };
+    match getaddrinfo(Some(host), None, Some(host_hints())) {
+        Ok(addrs) => addrs.collect_ips().map(|vec| vec.into_iter()),
+        Err(e) => {
+            reload_dns_nameserver();
+            Err(e)?
+        }
+    }
+}
+
+/// Lookup the address for a given hostname via DNS, filtering and ordering
+/// the results according to `options`.
+///
+/// Returns an iterator of IP Addresses, or an `io::Error` on failure.
+///
+/// ```rust
+///   use dns_lookup::{lookup_host_with, FamilyPolicy, LookupOptions};
+///
+///   let options = LookupOptions {
+///     family: FamilyPolicy::Ipv4Only,
+///     dedup: true,
+///     ..LookupOptions::default()
+///   };
+///   let ips = lookup_host_with("localhost", options).unwrap().collect::<Vec<_>>();
+///   assert!(ips.iter().all(|ip| ip.is_ipv4()));
+/// ```
+pub fn lookup_host_with(
+    host: &str,
+    options: LookupOptions,
+) -> io::Result<impl Iterator<Item = IpAddr> + use<>> {
+    let mut hints = host_hints();
+    match options.family {
+        FamilyPolicy::Ipv4Only => hints.address = AddrFamily::Inet.into(),
+        FamilyPolicy::Ipv6Only => hints.address = AddrFamily::Inet6.into(),
+        _ => {}
+    }
 
-    match getaddrinfo(Some(host), None, Some(hints)) {
-        Ok(addrs) => addrs
-            .map(|r| r.map(|a| a.sockaddr.ip()))
-            .collect::<io::Result<Vec<_>>>()
-            .map(|vec| vec.into_iter()),
+    let mut ips = match getaddrinfo(Some(host), None, Some(hints)) {
+        Ok(addrs) => addrs.collect_ips()?,
         Err(e) => {
             reload_dns_nameserver();
             Err(e)?
         }
+    };
+
+    if options.dedup {
+        let mut seen = HashSet::new();
+        ips.retain(|ip| seen.insert(*ip));
+    }
+    if options.shuffle {
+        shuffle(&mut ips);
     }
+    match options.family {
+        FamilyPolicy::Any => {}
+        FamilyPolicy::PreferIpv4 => ips.sort_by_key(IpAddr::is_ipv6),
+        FamilyPolicy::PreferIpv6 => ips.sort_by_key(IpAddr::is_ipv4),
+        FamilyPolicy::Ipv4Only => ips.retain(IpAddr::is_ipv4),
+        FamilyPolicy::Ipv6Only => ips.retain(IpAddr::is_ipv6),
+        FamilyPolicy::Interleave => ips = interleave(ips, IpAddr::is_ipv6),
+    }
+
+    Ok(ips.into_iter())
 }
 
 /// Lookup the hostname of a given IP Address via DNS.
@@ -38,8 +120,7 @@ pub fn lookup_host(host: &str) -> io::Result<impl Iterator<Item = IpAddr> + use<
 /// Returns the hostname as a String, or an `io::Error` on failure or if the hostname cannot be determined.
 pub fn lookup_addr(addr: &IpAddr) -> io::Result<String> {
     let sock = (*addr, 0).into();
-    #[allow(clippy::unnecessary_cast)]
-    match getnameinfo(&sock, (NI_NUMERICSERV | NI_NAMEREQD) as i32) {
+    match getnameinfo(&sock, NameInfoFlags::NUMERICSERV | NameInfoFlags::NAMEREQD) {
         Ok((name, _)) => Ok(name),
         Err(e) => {
             reload_dns_nameserver();
@@ -48,11 +129,55 @@ pub fn lookup_addr(addr: &IpAddr) -> io::Result<String> {
     }
 }
 
+// Hints used when looking up a bare hostname. Restricting to a single
+// socktype stops libc returning each address once per socktype.
+pub(crate) fn host_hints() -> AddrInfoHints {
+    #[allow(clippy::unnecessary_cast)]
+    AddrInfoHints {
+        socktype: SOCK_STREAM as i32,
+        ..AddrInfoHints::default()
+    }
+}
+
+// Interleave address families, starting with the family of the first
+// item (RFC 8305 section 4).
+pub(crate) fn interleave<T, F: Fn(&T) -> bool>(items: Vec<T>, is_ipv6: F) -> Vec<T> {
+    let first_v6 = items.first().is_some_and(&is_ipv6);
+    let (mut first, mut second): (Vec<_>, Vec<_>) =
+        items.into_iter().partition(|a| is_ipv6(a) == first_v6);
+    let mut out = Vec::with_capacity(first.len() + second.len());
+    first.reverse();
+    second.reverse();
+    loop {
+        match (first.pop(), second.pop()) {
+            (None, None) => break,
+            (a, b) => out.extend(a.into_iter().chain(b)),
+        }
+    }
+    out
+}
+
+// A random seed, for load balancing rather than anything security related.
+pub(crate) fn random_seed() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+// Fisher-Yates shuffle, using a xorshift generator.
+fn shuffle<T>(items: &mut [T]) {
+    let mut state = random_seed() | 1;
+    for i in (1..items.len()).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        items.swap(i, (state % (i as u64 + 1)) as usize);
+    }
+}
+
 // The lookup failure could be caused by using a stale /etc/resolv.conf.
 // See https://github.com/rust-lang/rust/issues/41570.
 // We therefore force a reload of the nameserver information.
 // MacOS and IOS don't seem to have this problem.
-fn reload_dns_nameserver() {
+pub(crate) fn reload_dns_nameserver() {
     cfg_if::cfg_if! {
       if #[cfg(target_os = "macos")] {
       } else if #[cfg(target_os = "ios")] {
@@ -74,6 +199,45 @@ fn test_localhost() {
     assert!(!ips.contains(&IpAddr::V4("10.0.0.1".parse().unwrap())));
 }
 
+#[test]
+fn test_lookup_host_with() {
+    let options = LookupOptions {
+        family: FamilyPolicy::Ipv4Only,
+        shuffle: true,
+        dedup: true,
+    };
+    let ips = lookup_host_with("localhost", options)
+        .unwrap()
+        .collect::<Vec<_>>();
+    assert!(ips.contains(&IpAddr::V4("127.0.0.1".parse().unwrap())));
+    assert!(ips.iter().all(IpAddr::is_ipv4));
+    for (i, ip) in ips.iter().enumerate() {
+        assert!(!ips[i + 1..].contains(ip));
+    }
+
+    let options = LookupOptions {
+        family: FamilyPolicy::PreferIpv6,
+        ..LookupOptions::default()
+    };
+    let ips = lookup_host_with("localhost", options)
+        .unwrap()
+        .collect::<Vec<_>>();
+    assert!(ips.windows(2).all(|w| w[0].is_ipv6() || w[1].is_ipv4()));
+}
+
+#[test]
+fn test_interleave() {
+    let ips = ["::1", "::2", "::3", "127.0.0.1", "127.0.0.2"]
+        .iter()
+        .map(|a| a.parse().unwrap())
+        .collect::<Vec<IpAddr>>();
+    let expected = ["::1", "127.0.0.1", "::2", "127.0.0.2", "::3"]
+        .iter()
+        .map(|a| a.parse().unwrap())
+        .collect::<Vec<IpAddr>>();
+    assert_eq!(interleave(ips, IpAddr::is_ipv6), expected);
+}
+
 #[cfg(unix)]
 #[test]
 fn test_rev_localhost() {
@@ -3,7 +3,6 @@ use std::ffi::CStr;
 use std::io;
 use std::net::SocketAddr;
 use std::os::raw::c_char;
-use std::str;
 
 #[cfg(unix)]
 use libc::{c_char as libc_c_char, getnameinfo as c_getnameinfo};
@@ -14,19 +13,59 @@ type libc_c_char = u8;
 #[cfg(windows)]
 use windows_sys::Win32::Networking::WinSock::getnameinfo as c_getnameinfo;
 
-use crate::err::LookupError;
+use crate::err::{last_errno, LookupError};
+use crate::types::NameInfoFlags;
 
 /// Retrieve the name for a given IP and Service. Acts as a thin wrapper around
 /// the libc getnameinfo.
 ///
-/// Returned names may be encoded in puny code for Interational Domain Names
+/// Returned names may be encoded in Punycode for Interational Domain Names
 /// (UTF8 DNS names). You can use the `idna` crate to decode these to their
 /// actual UTF8 representation.
 ///
-/// Retrieving names or services that contain non-UTF8 locales is currently not
-/// supported (as String is returned). Raise an issue if this is a concern for
-/// you.
-pub fn getnameinfo(sock: &SocketAddr, flags: i32) -> Result<(String, String), LookupError> {
+/// With the `idna` feature enabled, `xn--` labels in the returned name are
+/// decoded automatically. Names that fail UTS #46 processing return a
+/// `LookupErrorKind::Idna` error.
+///
+/// Flags can be given as `NameInfoFlags`, or as a raw `i32` using values
+/// from the libc on your system.
+///
+/// To retrieve names or services that aren't valid UTF8, such as those from
+/// non-UTF8 locales, use `getnameinfo_bytes`.
+pub fn getnameinfo<F: Into<NameInfoFlags>>(
+    sock: &SocketAddr,
+    flags: F,
+) -> Result<(String, String), LookupError> {
+    let (host, service) = getnameinfo_bytes(sock, flags)?;
+
+    let host = match String::from_utf8(host) {
+        Ok(name) => Ok(name),
+        Err(_) => Err(io::Error::other("Host UTF8 parsing failed")),
+    }?;
+
+    #[cfg(feature = "idna")]
+    let host = crate::idn::to_unicode(host)?;
+
+    let service = match String::from_utf8(service) {
+        Ok(service) => Ok(service),
+        Err(_) => Err(io::Error::other("Service UTF8 parsing failed")),
+    }?;
+
+    Ok((host, service))
+}
+
+/// Retrieve the name for a given IP and Service as raw bytes, without
+/// decoding them as UTF8 or Punycode. Otherwise identical to `getnameinfo`.
+///
+/// On unix, the results can be converted to an `OsString` using
+/// `std::os::unix::ffi::OsStringExt::from_vec`.
+pub fn getnameinfo_bytes<F: Into<NameInfoFlags>>(
+    sock: &SocketAddr,
+    flags: F,
+) -> Result<(Vec<u8>, Vec<u8>), LookupError> {
+    let flags = flags.into();
+    let ip = sock.ip().to_string();
+    let port = sock.port().to_string();
     // Convert the socket into our type, so we can get a sockaddr_in{,6} ptr.
     let sock: SockAddr = (*sock).into();
     let c_sock = sock.as_ptr();
@@ -43,32 +82,24 @@ pub fn getnameinfo(sock: &SocketAddr, flags: i32) -> Result<(String, String), Lo
     #[cfg(windows)]
     crate::win::init_winsock();
 
-    unsafe {
-        LookupError::match_gai_error(c_getnameinfo(
+    let err = unsafe {
+        c_getnameinfo(
             c_sock as *const _,
             c_sock_len,
             c_host.as_mut_ptr() as *mut libc_c_char,
             c_host.len() as _,
             c_service.as_mut_ptr() as *mut libc_c_char,
             c_service.len() as _,
-            flags,
-        ))?;
-    }
+            flags.bits(),
+        )
+    };
+    LookupError::match_gai_and_errno(err, last_errno())
+        .map_err(|e| e.with_query(Some(ip.as_bytes()), Some(port.as_bytes()), None))?;
 
     let host = unsafe { CStr::from_ptr(c_host.as_ptr() as *const c_char) };
     let service = unsafe { CStr::from_ptr(c_service.as_ptr() as *const c_char) };
 
-    let host = match str::from_utf8(host.to_bytes()) {
-        Ok(name) => Ok(name.to_owned()),
-        Err(_) => Err(io::Error::other("Host UTF8 parsing failed")),
-    }?;
-
-    let service = match str::from_utf8(service.to_bytes()) {
-        Ok(service) => Ok(service.to_owned()),
-        Err(_) => Err(io::Error::other("Service UTF8 parsing failed")),
-    }?;
-
-    Ok((host, service))
+    Ok((host.to_bytes().to_vec(), service.to_bytes().to_vec()))
 }
 
 #[test]
@@ -97,3 +128,17 @@ fn test_getnameinfo() {
         assert_eq!(name, hostname);
     }
 }
+
+#[test]
+fn test_getnameinfo_bytes() {
+    use std::net::{IpAddr, SocketAddr};
+
+    let ip: IpAddr = "127.0.0.1".parse().unwrap();
+    let socket: SocketAddr = (ip, 22).into();
+
+    let (name, service) = getnameinfo(&socket, 0).unwrap();
+    assert_eq!(
+        getnameinfo_bytes(&socket, 0).unwrap(),
+        (name.into_bytes(), service.into_bytes())
+    );
+}
@@ -0,0 +1,208 @@
+use std::io;
+use std::net::IpAddr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::addrinfo::getaddrinfo;
+use crate::err::LookupError;
+use crate::lookup::{host_hints, random_seed, reload_dns_nameserver};
+
+/// Retries lookups that fail with a transient error, using exponential
+/// backoff with jitter.
+///
+/// Only errors where `LookupError::is_transient` is true are retried, so a
+/// name that doesn't exist fails straight away. Nameserver information is
+/// reloaded between attempts, in case `/etc/resolv.conf` has changed.
+///
+/// ```rust
+///   use dns_lookup::RetryingResolver;
+///   use std::time::Duration;
+///
+///   let ips = RetryingResolver::new()
+///     .initial_backoff(Duration::from_millis(50))
+///     .max_elapsed(Duration::from_secs(5))
+///     .lookup_host("localhost")
+///     .unwrap();
+///   assert!(!ips.is_empty());
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RetryingResolver {
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: u32,
+    max_elapsed: Duration,
+    jitter: bool,
+}
+
+impl Default for RetryingResolver {
+    fn default() -> Self {
+        RetryingResolver {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            multiplier: 2,
+            max_elapsed: Duration::from_secs(30),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryingResolver {
+    /// Create a resolver that starts with a 100ms backoff, doubling up to
+    /// 5s, and gives up after 30s.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Time to wait after the first failed attempt.
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Longest time to wait between attempts.
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// Factor the backoff grows by after each failed attempt.
+    pub fn multiplier(mut self, multiplier: u32) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Stop retrying once this much time has passed since the first
+    /// attempt, returning the last error. A retry is not started if its
+    /// backoff would end after this time.
+    pub fn max_elapsed(mut self, max_elapsed: Duration) -> Self {
+        self.max_elapsed = max_elapsed;
+        self
+    }
+
+    /// Whether to randomise each backoff to between half and all of its
+    /// length, so that many clients failing together don't retry in step.
+    /// Enabled by default.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Lookup the addresses for a hostname like `lookup_host`, retrying
+    /// transient failures.
+    pub fn lookup_host(&self, host: &str) -> io::Result<Vec<IpAddr>> {
+        // Call getaddrinfo directly, so the error kind is kept for
+        // `is_transient`.
+        Ok(self.retry(|| {
+            let addrs = getaddrinfo(Some(host), None, Some(host_hints()))?;
+            Ok(addrs.collect_ips()?)
+        })?)
+    }
+
+    /// Run a lookup, retrying it while it fails with a transient error.
+    pub fn retry<T, F>(&self, mut lookup: F) -> Result<T, LookupError>
+    where
+        F: FnMut() -> Result<T, LookupError>,
+    {
+        let start = Instant::now();
+        let mut backoff = self.initial_backoff;
+        loop {
+            let err = match lookup() {
+                Ok(res) => return Ok(res),
+                Err(err) if err.is_transient() => err,
+                Err(err) => return Err(err),
+            };
+
+            let delay = if self.jitter {
+                jittered(backoff)
+            } else {
+                backoff
+            };
+            if start.elapsed() + delay > self.max_elapsed {
+                return Err(err);
+            }
+            thread::sleep(delay);
+            reload_dns_nameserver();
+            backoff = backoff
+                .saturating_mul(self.multiplier)
+                .min(self.max_backoff);
+        }
+    }
+}
+
+/// Lookup the addresses for a hostname like `lookup_host`, retrying
+/// transient failures with the default `RetryingResolver` options.
+pub fn retry_lookup(host: &str) -> io::Result<Vec<IpAddr>> {
+    RetryingResolver::new().lookup_host(host)
+}
+
+// Pick a random delay between half and all of the backoff.
+fn jittered(backoff: Duration) -> Duration {
+    let half = backoff / 2;
+    let nanos = u64::try_from(half.as_nanos()).unwrap_or(u64::MAX);
+    half + Duration::from_nanos(random_seed() % nanos.saturating_add(1))
+}
+
+#[test]
+fn test_retry() {
+    use crate::err::LookupErrorKind;
+
+    let resolver = RetryingResolver::new()
+        .initial_backoff(Duration::from_millis(1))
+        .max_backoff(Duration::from_millis(2));
+    let transient = || LookupError::from(io::Error::from(io::ErrorKind::TimedOut));
+
+    // Transient failures are retried until the lookup succeeds.
+    let mut attempts = 0;
+    let res = resolver.retry(|| {
+        attempts += 1;
+        match attempts {
+            3 => Ok(attempts),
+            _ => Err(transient()),
+        }
+    });
+    assert_eq!(res.unwrap(), 3);
+
+    // Permanent failures are returned straight away.
+    let mut attempts = 0;
+    let res: Result<(), _> = resolver.retry(|| {
+        attempts += 1;
+        Err(LookupError::invalid(LookupErrorKind::Badflags, "bad flags"))
+    });
+    assert!(res.is_err());
+    assert_eq!(attempts, 1);
+
+    // No retries fit in a zero maximum elapsed time.
+    let mut attempts = 0;
+    let res: Result<(), _> = resolver.max_elapsed(Duration::ZERO).retry(|| {
+        attempts += 1;
+        Err(transient())
+    });
+    assert!(res.is_err());
+    assert_eq!(attempts, 1);
+
+    // System errors are retried.
+    #[cfg(unix)]
+    {
+        let mut attempts = 0;
+        let res = resolver.retry(|| {
+            attempts += 1;
+            match attempts {
+                2 => Ok(attempts),
+                _ => Err(LookupError::from_gai_and_errno(
+                    libc::EAI_SYSTEM,
+                    libc::ECONNREFUSED,
+                )),
+            }
+        });
+        assert_eq!(res.unwrap(), 2);
+
+        // Even after a round trip through io::Error.
+        let err = LookupError::from_gai_and_errno(libc::EAI_SYSTEM, libc::ECONNREFUSED);
+        assert!(LookupError::from(io::Error::from(err)).is_transient());
+    }
+
+    for _ in 0..100 {
+        let delay = jittered(Duration::from_millis(10));
+        assert!(delay >= Duration::from_millis(5) && delay <= Duration::from_millis(10));
+    }
+}
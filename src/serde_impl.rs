@@ -0,0 +1,242 @@
+// Serialize and Deserialize for AddrInfo, AddrInfoHints, and the family,
+// socket type and protocol enums. Families, socket types and protocols are
+// written by name where this crate knows them, falling back to the raw
+// number, and unset values are written as none. Names are the same on
+// every platform, but a name without a variant here, such as `MPTCP`
+// outside Linux, fails to deserialize with an error saying so. Flags stay
+// as raw numbers.
+
+use std::fmt;
+use std::net::SocketAddr;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::addrinfo::{AddrInfo, AddrInfoHints};
+use crate::types::{AddrFamily, Protocol, SockType};
+
+// Write each variant as its name, and read it back from the variants
+// available on this platform.
+macro_rules! impl_by_name {
+    ($ty:ident, $what:literal) => {
+        impl Serialize for $ty {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(&format_args!("{self:?}"))
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let name = String::deserialize(deserializer)?;
+                by_name($ty::all(), &name).ok_or_else(|| {
+                    de::Error::custom(format!(
+                        concat!("Unknown ", $what, " '{}' on this platform"),
+                        name
+                    ))
+                })
+            }
+        }
+    };
+}
+
+impl_by_name!(SockType, "socket type");
+impl_by_name!(Protocol, "protocol");
+impl_by_name!(AddrFamily, "address family");
+
+fn by_name<T: Copy + fmt::Debug>(all: &[T], name: &str) -> Option<T> {
+    all.iter().copied().find(|v| format!("{v:?}") == name)
+}
+
+// A value written by name, or as a raw number if it has no name.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Named<T> {
+    Name(T),
+    Raw(i32),
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Named<T> {
+    // An untagged derive would hide why a name was rejected.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Name(String),
+            Raw(i32),
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Name(name) => {
+                T::deserialize(de::value::StringDeserializer::<D::Error>::new(name))
+                    .map(Named::Name)
+            }
+            Repr::Raw(raw) => Ok(Named::Raw(raw)),
+        }
+    }
+}
+
+impl<T: TryFrom<i32>> Named<T> {
+    fn new(raw: i32) -> Self {
+        T::try_from(raw).map_or(Named::Raw(raw), Named::Name)
+    }
+
+    // Families, socket types and protocols of 0 are unspecified, and
+    // written as none.
+    fn optional(raw: i32) -> Option<Self> {
+        (raw != 0).then(|| Named::new(raw))
+    }
+}
+
+impl<T: Into<i32>> Named<T> {
+    fn raw(self) -> i32 {
+        match self {
+            Named::Name(name) => name.into(),
+            Named::Raw(raw) => raw,
+        }
+    }
+}
+
+fn raw_or_zero<T: Into<i32>>(named: Option<Named<T>>) -> i32 {
+    named.map_or(0, Named::raw)
+}
+
+#[derive(Serialize, Deserialize)]
+struct HintsRepr {
+    flags: i32,
+    address: Option<Named<AddrFamily>>,
+    socktype: Option<Named<SockType>>,
+    protocol: Option<Named<Protocol>>,
+}
+
+impl Serialize for AddrInfoHints {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        HintsRepr {
+            flags: self.flags,
+            address: Named::optional(self.address),
+            socktype: Named::optional(self.socktype),
+            protocol: Named::optional(self.protocol),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for AddrInfoHints {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = HintsRepr::deserialize(deserializer)?;
+        Ok(AddrInfoHints {
+            flags: repr.flags,
+            address: raw_or_zero(repr.address),
+            socktype: raw_or_zero(repr.socktype),
+            protocol: raw_or_zero(repr.protocol),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct AddrInfoRepr {
+    flags: i32,
+    address: Option<Named<AddrFamily>>,
+    socktype: Option<Named<SockType>>,
+    protocol: Option<Named<Protocol>>,
+    sockaddr: SocketAddr,
+    canonname: Option<String>,
+}
+
+impl Serialize for AddrInfo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        AddrInfoRepr {
+            flags: self.flags,
+            address: Named::optional(self.address),
+            socktype: Named::optional(self.socktype),
+            protocol: Named::optional(self.protocol),
+            sockaddr: self.sockaddr,
+            canonname: self.canonname.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for AddrInfo {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = AddrInfoRepr::deserialize(deserializer)?;
+        let address = raw_or_zero(repr.address);
+        let family: i32 = match repr.sockaddr {
+            SocketAddr::V4(_) => AddrFamily::Inet.into(),
+            SocketAddr::V6(_) => AddrFamily::Inet6.into(),
+        };
+        if address != family {
+            return Err(de::Error::custom(
+                "Address family doesn't match the socket address",
+            ));
+        }
+        Ok(AddrInfo {
+            flags: repr.flags,
+            address,
+            socktype: raw_or_zero(repr.socktype),
+            protocol: raw_or_zero(repr.protocol),
+            sockaddr: repr.sockaddr,
+            canonname: repr.canonname,
+        })
+    }
+}
+
+#[test]
+fn test_serde_addrinfo() {
+    use crate::addrinfo::getaddrinfo;
+    use crate::types::AddrInfoFlags;
+
+    let hints = AddrInfoHints {
+        flags: AddrInfoFlags::CANONNAME.into(),
+        address: AddrFamily::Inet.into(),
+        socktype: SockType::Stream.into(),
+        protocol: 0,
+    };
+    let json = serde_json::to_value(hints).unwrap();
+    assert_eq!(json["address"], "Inet");
+    assert_eq!(json["socktype"], "Stream");
+    assert!(json["protocol"].is_null());
+    assert_eq!(
+        serde_json::from_value::<AddrInfoHints>(json).unwrap(),
+        hints
+    );
+
+    let addrs = getaddrinfo(Some("127.0.0.1"), Some("80"), Some(hints))
+        .unwrap()
+        .collect::<std::io::Result<Vec<_>>>()
+        .unwrap();
+    let json = serde_json::to_string(&addrs).unwrap();
+    assert!(json.contains(r#""sockaddr":"127.0.0.1:80""#));
+    assert_eq!(serde_json::from_str::<Vec<AddrInfo>>(&json).unwrap(), addrs);
+
+    // Values without a name are kept as raw numbers, and unset values are
+    // written as none.
+    let json = serde_json::to_value(AddrInfoHints {
+        socktype: 9999,
+        ..AddrInfoHints::default()
+    })
+    .unwrap();
+    assert_eq!(json["socktype"], 9999);
+    assert!(json["address"].is_null());
+    assert!(json["protocol"].is_null());
+    assert_eq!(
+        serde_json::from_value::<AddrInfoHints>(json)
+            .unwrap()
+            .socktype,
+        9999
+    );
+
+    // Names this platform doesn't know are rejected, saying which.
+    let err = serde_json::from_str::<AddrInfoHints>(
+        r#"{"flags": 0, "address": "Inet", "socktype": "Bogus", "protocol": null}"#,
+    )
+    .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Unknown socket type 'Bogus' on this platform"));
+    assert!(serde_json::from_str::<Protocol>(r#""Bogus""#).is_err());
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    assert!(serde_json::from_str::<Protocol>(r#""MPTCP""#).is_err());
+    assert_eq!(
+        serde_json::from_str::<Protocol>(r#""TCP""#).unwrap(),
+        Protocol::TCP
+    );
+}
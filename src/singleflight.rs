@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::io;
+use std::net::IpAddr;
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+
+use crate::addrinfo::{getaddrinfo, AddrInfo, AddrInfoHints};
+use crate::err::LookupError;
+use crate::lookup::{host_hints, reload_dns_nameserver};
+
+type Key = (Option<String>, Option<String>, Option<AddrInfoHints>);
+type CallResult = Result<Vec<AddrInfo>, LookupError>;
+
+// A single in-flight getaddrinfo call, shared by every thread asking
+// for the same query.
+#[derive(Default)]
+struct Call {
+    result: Mutex<Option<CallResult>>,
+    done: Condvar,
+}
+
+fn in_flight() -> &'static Mutex<HashMap<Key, Arc<Call>>> {
+    static IN_FLIGHT: OnceLock<Mutex<HashMap<Key, Arc<Call>>>> = OnceLock::new();
+    IN_FLIGHT.get_or_init(Default::default)
+}
+
+// Removes the call from the in-flight map and wakes waiters, even if
+// the leading thread unwinds before publishing a result.
+struct Leader<'a> {
+    key: &'a Key,
+    call: &'a Call,
+}
+
+impl Leader<'_> {
+    fn publish(self, res: &CallResult) {
+        *self.call.result.lock().unwrap() = Some(res.clone());
+    }
+}
+
+impl Drop for Leader<'_> {
+    fn drop(&mut self) {
+        in_flight().lock().unwrap().remove(self.key);
+        let mut result = self.call.result.lock().unwrap();
+        if result.is_none() {
+            *result = Some(Err(
+                io::Error::other("Coalesced lookup was abandoned").into()
+            ));
+        }
+        self.call.done.notify_all();
+    }
+}
+
+/// Retrieve socket information like `getaddrinfo`, sharing a single
+/// libc call between concurrent callers.
+///
+/// If another thread is already resolving the same `(host, service, hints)`
+/// combination, this waits for that query and returns a clone of its result
+/// or error, instead of issuing a second query. Results are not kept once
+/// the in-flight query completes, so this is not a cache.
+pub fn getaddrinfo_coalesced(
+    host: Option<&str>,
+    service: Option<&str>,
+    hints: Option<AddrInfoHints>,
+) -> Result<Vec<AddrInfo>, LookupError> {
+    let key = (host.map(str::to_owned), service.map(str::to_owned), hints);
+
+    let (call, leading) = {
+        let mut calls = in_flight().lock().unwrap();
+        match calls.get(&key) {
+            Some(call) => (call.clone(), false),
+            None => {
+                let call = Arc::new(Call::default());
+                calls.insert(key.clone(), call.clone());
+                (call, true)
+            }
+        }
+    };
+
+    if leading {
+        let leader = Leader {
+            key: &key,
+            call: &call,
+        };
+        let res = getaddrinfo(host, service, hints)
+            .and_then(|addrs| Ok(addrs.collect::<io::Result<Vec<_>>>()?));
+        leader.publish(&res);
+        return res;
+    }
+
+    let mut result = call.result.lock().unwrap();
+    while result.is_none() {
+        result = call.done.wait(result).unwrap();
+    }
+    result.as_ref().unwrap().clone()
+}
+
+/// Lookup the address for a given hostname via DNS, sharing a single
+/// query between concurrent callers. See `getaddrinfo_coalesced`.
+///
+/// Returns an iterator of IP Addresses, or an `io::Error` on failure.
+pub fn lookup_host_coalesced(host: &str) -> io::Result<impl Iterator<Item = IpAddr> + use<>> {
+    match getaddrinfo_coalesced(Some(host), None, Some(host_hints())) {
+        Ok(addrs) => Ok(addrs.into_iter().map(|a| a.sockaddr.ip())),
+        Err(e) => {
+            reload_dns_nameserver();
+            Err(e)?
+        }
+    }
+}
+
+#[test]
+fn test_coalesced_localhost() {
+    use std::thread;
+
+    let threads = (0..8)
+        .map(|_| thread::spawn(|| getaddrinfo_coalesced(Some("localhost"), None, None)))
+        .collect::<Vec<_>>();
+
+    let expected = getaddrinfo(Some("localhost"), None, None)
+        .unwrap()
+        .collect::<io::Result<Vec<_>>>()
+        .unwrap();
+    for t in threads {
+        assert_eq!(t.join().unwrap().unwrap(), expected);
+    }
+
+    let ips = lookup_host_coalesced("localhost")
+        .unwrap()
+        .collect::<Vec<_>>();
+    assert!(ips.contains(&"127.0.0.1".parse::<IpAddr>().unwrap()));
+}
@@ -0,0 +1,467 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+
+use crate::addrinfo::{AddrInfo, AddrInfoIter};
+
+/// An entry in an RFC 6724 policy table.
+///
+/// Addresses are matched against the longest matching prefix. IPv4
+/// addresses are matched as IPv4-mapped IPv6 addresses (`::ffff:0:0/96`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PolicyEntry {
+    /// Prefix matched by this entry.
+    pub prefix: Ipv6Addr,
+    /// Length of the prefix in bits, up to 128.
+    pub prefix_len: u8,
+    /// Precedence of matching destinations. Higher values are preferred.
+    pub precedence: u32,
+    /// Label of matching addresses. Destinations whose label matches their
+    /// source address's label are preferred.
+    pub label: u32,
+}
+
+impl PolicyEntry {
+    fn matches(&self, addr: u128) -> bool {
+        prefix_matches(self.prefix, self.prefix_len, addr)
+    }
+}
+
+/// An entry overriding the scope of IPv4 addresses, like `scopev4` in
+/// gai.conf.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ScopeEntry {
+    /// Prefix matched by this entry.
+    pub prefix: Ipv4Addr,
+    /// Length of the prefix in bits, up to 32.
+    pub prefix_len: u8,
+    /// Scope of matching addresses, as defined in RFC 4291 section 2.7.
+    pub scope: u8,
+}
+
+/// Policy table used to sort destination addresses, as described in
+/// RFC 6724 section 2.1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PolicyTable {
+    entries: Vec<PolicyEntry>,
+    scopev4: Vec<ScopeEntry>,
+}
+
+impl PolicyTable {
+    /// Create a policy table from the given entries.
+    ///
+    /// Addresses that match no entry get precedence and label `0`.
+    pub fn new(entries: Vec<PolicyEntry>) -> Self {
+        PolicyTable {
+            entries,
+            scopev4: Vec::new(),
+        }
+    }
+
+    /// Override the scope of IPv4 addresses matching the given entries.
+    ///
+    /// IPv4 addresses matching no entry use the scopes from RFC 6724
+    /// section 3.2.
+    pub fn with_scopev4(mut self, scopev4: Vec<ScopeEntry>) -> Self {
+        self.scopev4 = scopev4;
+        self
+    }
+
+    /// Entries in this policy table.
+    pub fn entries(&self) -> &[PolicyEntry] {
+        &self.entries
+    }
+
+    /// IPv4 scope overrides in this policy table.
+    pub fn scopev4(&self) -> &[ScopeEntry] {
+        &self.scopev4
+    }
+
+    // Find the entry with the longest prefix matching the address.
+    fn lookup(&self, addr: &IpAddr) -> Option<&PolicyEntry> {
+        let addr = u128::from(to_ipv6(addr));
+        self.entries
+            .iter()
+            .filter(|e| e.matches(addr))
+            .max_by_key(|e| e.prefix_len)
+    }
+
+    fn precedence(&self, addr: &IpAddr) -> u32 {
+        self.lookup(addr).map_or(0, |e| e.precedence)
+    }
+
+    fn label(&self, addr: &IpAddr) -> u32 {
+        self.lookup(addr).map_or(0, |e| e.label)
+    }
+
+    fn scope(&self, addr: &IpAddr) -> u8 {
+        let IpAddr::V4(v4) = addr else {
+            return scope(addr);
+        };
+        let mapped = u128::from(v4.to_ipv6_mapped());
+        self.scopev4
+            .iter()
+            .filter(|e| {
+                prefix_matches(e.prefix.to_ipv6_mapped(), e.prefix_len.min(32) + 96, mapped)
+            })
+            .max_by_key(|e| e.prefix_len)
+            .map_or_else(|| scope(addr), |e| e.scope)
+    }
+}
+
+impl Default for PolicyTable {
+    /// The default policy table from RFC 6724 section 2.1.
+    fn default() -> Self {
+        let entry = |prefix: &str, prefix_len, precedence, label| PolicyEntry {
+            prefix: prefix.parse().unwrap(),
+            prefix_len,
+            precedence,
+            label,
+        };
+        PolicyTable::new(vec![
+            entry("::1", 128, 50, 0),
+            entry("::", 0, 40, 1),
+            entry("::ffff:0:0", 96, 35, 4),
+            entry("2002::", 16, 30, 2),
+            entry("2001::", 32, 5, 5),
+            entry("fc00::", 7, 3, 13),
+            entry("::", 96, 1, 3),
+            entry("fec0::", 10, 1, 11),
+            entry("3ffe::", 16, 1, 12),
+        ])
+    }
+}
+
+/// Sorts destination addresses into the order recommended by RFC 6724.
+///
+/// The source address for each destination is found by connecting an
+/// unbound UDP socket to it, which sends no packets. This is done once per
+/// distinct address, even if it appears once for each socket type.
+/// Destinations without a usable source address are sorted last.
+///
+/// Rules 3, 4 and 7 (deprecated, home and native addresses) need interface
+/// details that aren't portably available, so they're not applied.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AddressSorter {
+    policy: PolicyTable,
+}
+
+impl AddressSorter {
+    /// Create a sorter using the default RFC 6724 policy table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a sorter using the given policy table.
+    pub fn with_policy(policy: PolicyTable) -> Self {
+        AddressSorter { policy }
+    }
+
+    /// The policy table used by this sorter.
+    pub fn policy(&self) -> &PolicyTable {
+        &self.policy
+    }
+
+    /// Sort `getaddrinfo` results in place.
+    pub fn sort(&self, addrs: &mut [AddrInfo]) {
+        self.sort_with(addrs, |a| a.sockaddr, probe_source);
+    }
+
+    /// Collect and sort the results of `getaddrinfo`.
+    pub fn sort_iter(&self, iter: AddrInfoIter) -> io::Result<Vec<AddrInfo>> {
+        let mut addrs = iter.collect::<io::Result<Vec<_>>>()?;
+        self.sort(&mut addrs);
+        Ok(addrs)
+    }
+
+    /// Sort socket addresses in place.
+    pub fn sort_socket_addrs(&self, addrs: &mut [SocketAddr]) {
+        self.sort_with(addrs, |a| *a, probe_source);
+    }
+
+    // Sort items by their destination address, using `source` to find the
+    // source address that would be used to reach each one. The sort is
+    // stable, so rule 10 (leave the order unchanged) applies to ties.
+    fn sort_with<T, A, S>(&self, items: &mut [T], addr: A, mut source: S)
+    where
+        A: Fn(&T) -> SocketAddr,
+        S: FnMut(&SocketAddr) -> Option<IpAddr>,
+    {
+        let mut sources = HashMap::new();
+        let keys = items
+            .iter()
+            .map(|item| {
+                let dest = addr(item);
+                let src = *sources.entry(dest).or_insert_with(|| source(&dest));
+                self.destination(dest.ip(), src)
+            })
+            .collect::<Vec<_>>();
+        let mut order = (0..items.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| compare(&keys[a], &keys[b]));
+
+        // Move each item into its sorted position. Items before `i` have
+        // already been moved, so follow the swaps to find where the item
+        // for position `i` is now.
+        for i in 0..order.len() {
+            let mut j = order[i];
+            while j < i {
+                j = order[j];
+            }
+            items.swap(i, j);
+        }
+    }
+
+    fn destination(&self, addr: IpAddr, source: Option<IpAddr>) -> Destination {
+        Destination {
+            addr,
+            scope: self.policy.scope(&addr),
+            precedence: self.policy.precedence(&addr),
+            label: self.policy.label(&addr),
+            source: source.map(|s| Source {
+                addr: s,
+                scope: self.policy.scope(&s),
+                label: self.policy.label(&s),
+            }),
+        }
+    }
+}
+
+impl AddrInfoIter {
+    /// Collect these results, and sort them into RFC 6724 order using the
+    /// default policy table. See `AddressSorter`.
+    pub fn sorted(self) -> io::Result<Vec<AddrInfo>> {
+        AddressSorter::new().sort_iter(self)
+    }
+}
+
+struct Source {
+    addr: IpAddr,
+    scope: u8,
+    label: u32,
+}
+
+struct Destination {
+    addr: IpAddr,
+    scope: u8,
+    precedence: u32,
+    label: u32,
+    source: Option<Source>,
+}
+
+// Compare two destinations using the rules in RFC 6724 section 6. `Less`
+// means `a` is preferred.
+fn compare(a: &Destination, b: &Destination) -> Ordering {
+    // Rule 1: Avoid unusable destinations.
+    let (sa, sb) = match (&a.source, &b.source) {
+        (Some(sa), Some(sb)) => (sa, sb),
+        (Some(_), None) => return Ordering::Less,
+        (None, Some(_)) => return Ordering::Greater,
+        (None, None) => {
+            // Rules 6 and 8 don't depend on the source address.
+            return b.precedence.cmp(&a.precedence).then(a.scope.cmp(&b.scope));
+        }
+    };
+
+    // Rule 2: Prefer matching scope.
+    prefer(a.scope == sa.scope, b.scope == sb.scope)
+        // Rule 5: Prefer matching label.
+        .then(prefer(a.label == sa.label, b.label == sb.label))
+        // Rule 6: Prefer higher precedence.
+        .then(b.precedence.cmp(&a.precedence))
+        // Rule 8: Prefer smaller scope.
+        .then(a.scope.cmp(&b.scope))
+        // Rule 9: Use longest matching prefix.
+        .then_with(|| {
+            if a.addr.is_ipv6() != b.addr.is_ipv6() {
+                return Ordering::Equal;
+            }
+            common_prefix_len(&b.addr, &sb.addr).cmp(&common_prefix_len(&a.addr, &sa.addr))
+        })
+}
+
+fn prefer(a: bool, b: bool) -> Ordering {
+    b.cmp(&a)
+}
+
+// Scope values from RFC 4291 section 2.7, with IPv4 addresses mapped as
+// described in RFC 6724 section 3.2.
+const SCOPE_LINK_LOCAL: u8 = 0x2;
+const SCOPE_SITE_LOCAL: u8 = 0x5;
+const SCOPE_GLOBAL: u8 = 0xe;
+
+fn scope(addr: &IpAddr) -> u8 {
+    match addr {
+        IpAddr::V4(v4) if v4.is_loopback() || v4.is_link_local() => SCOPE_LINK_LOCAL,
+        IpAddr::V4(_) => SCOPE_GLOBAL,
+        IpAddr::V6(v6) => {
+            let segments = v6.segments();
+            if v6.is_multicast() {
+                (segments[0] & 0x000f) as u8
+            } else if v6.is_loopback() || segments[0] & 0xffc0 == 0xfe80 {
+                SCOPE_LINK_LOCAL
+            } else if segments[0] & 0xffc0 == 0xfec0 {
+                SCOPE_SITE_LOCAL
+            } else {
+                SCOPE_GLOBAL
+            }
+        }
+    }
+}
+
+fn to_ipv6(addr: &IpAddr) -> Ipv6Addr {
+    match addr {
+        IpAddr::V4(v4) => v4.to_ipv6_mapped(),
+        IpAddr::V6(v6) => *v6,
+    }
+}
+
+pub(crate) fn prefix_matches(prefix: Ipv6Addr, prefix_len: u8, addr: u128) -> bool {
+    let mask = u128::MAX
+        .checked_shl(128 - u32::from(prefix_len.min(128)))
+        .unwrap_or(0);
+    u128::from(prefix) & mask == addr & mask
+}
+
+// Length of the common prefix of two addresses of the same family. For
+// IPv6 this only counts the prefix (first 64 bits), as RFC 6724 section 5
+// recommends.
+fn common_prefix_len(a: &IpAddr, b: &IpAddr) -> u32 {
+    match (a, b) {
+        (IpAddr::V4(a), IpAddr::V4(b)) => (u32::from(*a) ^ u32::from(*b)).leading_zeros(),
+        (IpAddr::V6(a), IpAddr::V6(b)) => (u128::from(*a) ^ u128::from(*b)).leading_zeros().min(64),
+        _ => 0,
+    }
+}
+
+// Find the source address the kernel would use to reach a destination.
+// Connecting a UDP socket only selects a route, and sends no packets.
+fn probe_source(dest: &SocketAddr) -> Option<IpAddr> {
+    let bind: SocketAddr = match dest {
+        SocketAddr::V4(_) => (IpAddr::from([0, 0, 0, 0]), 0).into(),
+        SocketAddr::V6(_) => (IpAddr::from([0_u16; 8]), 0).into(),
+    };
+    let mut dest = *dest;
+    if dest.port() == 0 {
+        dest.set_port(9);
+    }
+    let socket = UdpSocket::bind(bind).ok()?;
+    socket.connect(dest).ok()?;
+    socket.local_addr().ok().map(|a| a.ip())
+}
+
+#[test]
+fn test_policy_table() {
+    let table = PolicyTable::default();
+    let check = |addr: &str, precedence, label| {
+        let addr: IpAddr = addr.parse().unwrap();
+        assert_eq!(table.precedence(&addr), precedence, "{addr}");
+        assert_eq!(table.label(&addr), label, "{addr}");
+    };
+    check("::1", 50, 0);
+    check("2001:0:5ef5:79fd::1", 5, 5);
+    check("2001:db8::1", 40, 1);
+    check("2a00::1", 40, 1);
+    check("10.0.0.1", 35, 4);
+    check("2002:c000:0204::1", 30, 2);
+    check("fd00::1", 3, 13);
+}
+
+#[test]
+fn test_sort_rules() {
+    let sorter = AddressSorter::new();
+    let parse = |addrs: &[&str]| {
+        addrs
+            .iter()
+            .map(|a| SocketAddr::new(a.parse().unwrap(), 80))
+            .collect::<Vec<_>>()
+    };
+
+    // Rule 1: unreachable destinations go last.
+    let mut addrs = parse(&["2001:db8::1", "198.51.100.1"]);
+    sorter.sort_with(&mut addrs, |a| *a, |d| d.is_ipv4().then(|| d.ip()));
+    assert_eq!(addrs, parse(&["198.51.100.1", "2001:db8::1"]));
+
+    // Rule 2: prefer matching scope. A global IPv6 destination reached from
+    // a link-local source loses to IPv4.
+    let mut addrs = parse(&["2a00::1", "198.51.100.1"]);
+    sorter.sort_with(
+        &mut addrs,
+        |a| *a,
+        |d| match d.ip() {
+            IpAddr::V6(_) => Some("fe80::1".parse().unwrap()),
+            v4 => Some(v4),
+        },
+    );
+    assert_eq!(addrs, parse(&["198.51.100.1", "2a00::1"]));
+
+    // Rule 6: prefer higher precedence when sources match.
+    let mut addrs = parse(&["127.0.0.1", "::1"]);
+    sorter.sort_with(&mut addrs, |a| *a, |d| Some(d.ip()));
+    assert_eq!(addrs, parse(&["::1", "127.0.0.1"]));
+
+    // Rule 9: longest matching prefix.
+    let mut addrs = parse(&["2a00:1::1", "2a00:2::1"]);
+    let source = "2a00:2::ff".parse().unwrap();
+    sorter.sort_with(&mut addrs, |a| *a, |_| Some(source));
+    assert_eq!(addrs, parse(&["2a00:2::1", "2a00:1::1"]));
+
+    // A policy table preferring IPv4 reverses rule 6.
+    let mut entries = PolicyTable::default().entries().to_vec();
+    entries[2].precedence = 100;
+    let sorter = AddressSorter::with_policy(PolicyTable::new(entries));
+    let mut addrs = parse(&["2a00::1", "198.51.100.1"]);
+    sorter.sort_with(&mut addrs, |a| *a, |d| Some(d.ip()));
+    assert_eq!(addrs, parse(&["198.51.100.1", "2a00::1"]));
+}
+
+#[test]
+fn test_sort_order() {
+    let sorter = AddressSorter::new();
+    let parse = |addrs: &[&str]| {
+        addrs
+            .iter()
+            .map(|a| SocketAddr::new(a.parse().unwrap(), 80))
+            .collect::<Vec<_>>()
+    };
+
+    // Each distinct address is only probed once, and ties keep their order.
+    let mut probes = 0;
+    let mut addrs = parse(&[
+        "198.51.100.1",
+        "2001:db8::1",
+        "198.51.100.1",
+        "198.51.100.2",
+        "2001:db8::1",
+        "198.51.100.1",
+    ]);
+    sorter.sort_with(
+        &mut addrs,
+        |a| *a,
+        |d| {
+            probes += 1;
+            d.is_ipv4().then(|| d.ip())
+        },
+    );
+    assert_eq!(probes, 3);
+    assert_eq!(
+        addrs,
+        parse(&[
+            "198.51.100.1",
+            "198.51.100.1",
+            "198.51.100.2",
+            "198.51.100.1",
+            "2001:db8::1",
+            "2001:db8::1",
+        ])
+    );
+}
+
+#[test]
+fn test_sort_localhost() {
+    let addrs = crate::getaddrinfo(Some("localhost"), None, None)
+        .unwrap()
+        .sorted()
+        .unwrap();
+    assert!(addrs.iter().any(|a| a.sockaddr.ip().is_loopback()));
+}
@@ -1,3 +1,6 @@
+use std::io;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign};
+
 #[cfg(unix)]
 use libc as c;
 
@@ -12,7 +15,8 @@ type c_int = i32;
 ///
 /// Cross platform enum of common Socket Types. For missing types use
 /// the `libc` and `windows-sys` crates, depending on platform.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum SockType {
     /// Sequenced, reliable, connection-based byte streams.
     Stream,
@@ -24,6 +28,8 @@ pub enum SockType {
     /// Reliably-delivered messages.
     #[cfg(not(target_os = "redox"))]
     RDM,
+    /// Sequenced, reliable, connection-based datagrams of fixed max length.
+    SeqPacket,
 }
 
 impl From<SockType> for c_int {
@@ -36,11 +42,46 @@ impl From<SockType> for c_int {
             SockType::Raw => c::SOCK_RAW,
             #[cfg(not(target_os = "redox"))]
             SockType::RDM => c::SOCK_RDM,
+            SockType::SeqPacket => c::SOCK_SEQPACKET,
         })
         .into()
     }
 }
 
+impl SockType {
+    // Every variant available on this platform.
+    pub(crate) fn all() -> &'static [SockType] {
+        &[
+            SockType::Stream,
+            SockType::DGram,
+            #[cfg(not(target_os = "redox"))]
+            SockType::Raw,
+            #[cfg(not(target_os = "redox"))]
+            SockType::RDM,
+            SockType::SeqPacket,
+        ]
+    }
+}
+
+impl TryFrom<c_int> for SockType {
+    type Error = io::Error;
+
+    /// Convert a raw `ai_socktype` value, returning an error for values
+    /// without a matching variant.
+    fn try_from(value: c_int) -> io::Result<SockType> {
+        SockType::all()
+            .iter()
+            .find(|s| **s == value)
+            .copied()
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown socket type: {value}"),
+                )
+            })
+    }
+}
+
 impl PartialEq<c_int> for SockType {
     fn eq(&self, other: &c_int) -> bool {
         let int: c_int = (*self).into();
@@ -59,7 +100,8 @@ impl PartialEq<SockType> for c_int {
 ///
 /// Cross platform enum of common Socket Protocols. For missing types use
 /// the `libc` and `windows-sys` crates, depending on platform.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum Protocol {
     /// Internet Control Message Protocol.
     ICMP,
@@ -67,6 +109,24 @@ pub enum Protocol {
     TCP,
     /// User Datagram Protocol.
     UDP,
+    /// Stream Control Transmission Protocol.
+    #[cfg(any(
+        windows,
+        target_os = "linux",
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_vendor = "apple"
+    ))]
+    SCTP,
+    /// Internet Control Message Protocol for IPv6.
+    ICMPv6,
+    /// Lightweight User Datagram Protocol.
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+    UDPLite,
+    /// Multipath Transmission Control Protocol.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    MPTCP,
 }
 
 impl From<Protocol> for c_int {
@@ -76,11 +136,69 @@ impl From<Protocol> for c_int {
             Protocol::ICMP => c::IPPROTO_ICMP,
             Protocol::TCP => c::IPPROTO_TCP,
             Protocol::UDP => c::IPPROTO_UDP,
+            #[cfg(any(
+                windows,
+                target_os = "linux",
+                target_os = "android",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_vendor = "apple"
+            ))]
+            Protocol::SCTP => c::IPPROTO_SCTP,
+            Protocol::ICMPv6 => c::IPPROTO_ICMPV6,
+            #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+            Protocol::UDPLite => c::IPPROTO_UDPLITE,
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Protocol::MPTCP => c::IPPROTO_MPTCP,
         })
         .into()
     }
 }
 
+impl Protocol {
+    // Every variant available on this platform.
+    pub(crate) fn all() -> &'static [Protocol] {
+        &[
+            Protocol::ICMP,
+            Protocol::TCP,
+            Protocol::UDP,
+            #[cfg(any(
+                windows,
+                target_os = "linux",
+                target_os = "android",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_vendor = "apple"
+            ))]
+            Protocol::SCTP,
+            Protocol::ICMPv6,
+            #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+            Protocol::UDPLite,
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            Protocol::MPTCP,
+        ]
+    }
+}
+
+impl TryFrom<c_int> for Protocol {
+    type Error = io::Error;
+
+    /// Convert a raw `ai_protocol` value, returning an error for values
+    /// without a matching variant.
+    fn try_from(value: c_int) -> io::Result<Protocol> {
+        Protocol::all()
+            .iter()
+            .find(|p| **p == value)
+            .copied()
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown protocol: {value}"),
+                )
+            })
+    }
+}
+
 impl PartialEq<c_int> for Protocol {
     fn eq(&self, other: &c_int) -> bool {
         let int: c_int = (*self).into();
@@ -99,28 +217,77 @@ impl PartialEq<Protocol> for c_int {
 ///
 /// Cross platform enum of common Address Families. For missing types use
 /// the `libc` and `windows-sys` crates, depending on platform.
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum AddrFamily {
+    /// Unspecified, allowing any address family.
+    Unspec,
     /// Local to host (pipes and file-domain)
     Unix,
     /// IP protocol family.
     Inet,
     /// IP version 6.
     Inet6,
+    /// Kernel user interface device.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    Netlink,
+    /// Low level packet interface.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    Packet,
 }
 
 impl From<AddrFamily> for c_int {
     fn from(sock: AddrFamily) -> c_int {
         #[allow(clippy::useless_conversion)]
         (match sock {
+            AddrFamily::Unspec => c::AF_UNSPEC,
             AddrFamily::Unix => c::AF_UNIX,
             AddrFamily::Inet => c::AF_INET,
             AddrFamily::Inet6 => c::AF_INET6,
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            AddrFamily::Netlink => c::AF_NETLINK,
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            AddrFamily::Packet => c::AF_PACKET,
         })
         .into()
     }
 }
 
+impl AddrFamily {
+    // Every variant available on this platform.
+    pub(crate) fn all() -> &'static [AddrFamily] {
+        &[
+            AddrFamily::Unspec,
+            AddrFamily::Unix,
+            AddrFamily::Inet,
+            AddrFamily::Inet6,
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            AddrFamily::Netlink,
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            AddrFamily::Packet,
+        ]
+    }
+}
+
+impl TryFrom<c_int> for AddrFamily {
+    type Error = io::Error;
+
+    /// Convert a raw `ai_family` value, returning an error for values
+    /// without a matching variant.
+    fn try_from(value: c_int) -> io::Result<AddrFamily> {
+        AddrFamily::all()
+            .iter()
+            .find(|a| **a == value)
+            .copied()
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unknown address family: {value}"),
+                )
+            })
+    }
+}
+
 impl PartialEq<c_int> for AddrFamily {
     fn eq(&self, other: &c_int) -> bool {
         let int: c_int = (*self).into();
@@ -134,3 +301,244 @@ impl PartialEq<AddrFamily> for c_int {
         *self == int
     }
 }
+
+#[test]
+fn test_try_from() {
+    for sock in SockType::all() {
+        assert_eq!(SockType::try_from(c_int::from(*sock)).unwrap(), *sock);
+    }
+    for proto in Protocol::all() {
+        assert_eq!(Protocol::try_from(c_int::from(*proto)).unwrap(), *proto);
+    }
+    for family in AddrFamily::all() {
+        assert_eq!(AddrFamily::try_from(c_int::from(*family)).unwrap(), *family);
+    }
+    assert_eq!(SockType::SeqPacket, c::SOCK_SEQPACKET);
+    assert_eq!(c_int::from(AddrFamily::Unspec), 0);
+    assert!(SockType::try_from(-1).is_err());
+    assert!(Protocol::try_from(-1).is_err());
+    assert!(AddrFamily::try_from(-1).is_err());
+}
+
+// Shared methods and operators for bitflag types wrapping a c_int.
+macro_rules! impl_flags {
+    ($name:ident) => {
+        impl $name {
+            /// No flags set.
+            pub const fn empty() -> Self {
+                $name(0)
+            }
+
+            /// The raw platform value of these flags.
+            pub const fn bits(self) -> c_int {
+                self.0
+            }
+
+            /// Create flags from a raw platform value. Bits that don't match
+            /// a known flag are kept as-is.
+            pub const fn from_bits_retain(bits: c_int) -> Self {
+                $name(bits)
+            }
+
+            /// Whether no flags are set.
+            pub const fn is_empty(self) -> bool {
+                self.0 == 0
+            }
+
+            /// Whether all flags in `other` are set.
+            pub const fn contains(self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            /// Set the flags in `other`.
+            pub fn insert(&mut self, other: Self) {
+                self.0 |= other.0;
+            }
+
+            /// Clear the flags in `other`.
+            pub fn remove(&mut self, other: Self) {
+                self.0 &= !other.0;
+            }
+        }
+
+        impl BitOr for $name {
+            type Output = Self;
+            fn bitor(self, other: Self) -> Self {
+                $name(self.0 | other.0)
+            }
+        }
+
+        impl BitOrAssign for $name {
+            fn bitor_assign(&mut self, other: Self) {
+                self.0 |= other.0;
+            }
+        }
+
+        impl BitAnd for $name {
+            type Output = Self;
+            fn bitand(self, other: Self) -> Self {
+                $name(self.0 & other.0)
+            }
+        }
+
+        impl BitAndAssign for $name {
+            fn bitand_assign(&mut self, other: Self) {
+                self.0 &= other.0;
+            }
+        }
+
+        impl From<$name> for c_int {
+            fn from(flags: $name) -> c_int {
+                flags.0
+            }
+        }
+
+        impl From<c_int> for $name {
+            fn from(bits: c_int) -> $name {
+                $name(bits)
+            }
+        }
+    };
+}
+
+/// Flags for `getaddrinfo`
+///
+/// Cross platform bitflags for the `ai_flags` field of `AddrInfoHints`,
+/// which can be combined with `|`. Values are converted to the platform's
+/// `AI_*` constants.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AddrInfoFlags(c_int);
+
+// Not every libc defines every flag, for example solaris and illumos only
+// define AI_CANONNAME, so flags are grouped by the platforms that define
+// them.
+#[allow(clippy::unnecessary_cast)]
+impl AddrInfoFlags {
+    /// Return the canonical name of the host in the first result.
+    pub const CANONNAME: Self = AddrInfoFlags(c::AI_CANONNAME as c_int);
+}
+
+#[cfg(any(
+    windows,
+    target_vendor = "apple",
+    target_env = "newlib",
+    target_os = "linux",
+    target_os = "l4re",
+    target_os = "android",
+    target_os = "emscripten",
+    target_os = "fuchsia",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "aix",
+    target_os = "cygwin",
+    target_os = "hurd",
+    target_os = "nto",
+    target_os = "redox",
+))]
+#[allow(clippy::unnecessary_cast)]
+impl AddrInfoFlags {
+    /// Return addresses suitable for binding a listening socket.
+    pub const PASSIVE: Self = AddrInfoFlags(c::AI_PASSIVE as c_int);
+    /// The host must be a numeric address, and no lookup is done.
+    pub const NUMERICHOST: Self = AddrInfoFlags(c::AI_NUMERICHOST as c_int);
+    /// The service must be a numeric port, and no lookup is done.
+    pub const NUMERICSERV: Self = AddrInfoFlags(c::AI_NUMERICSERV as c_int);
+}
+
+// The BSDs, newlib and QNX don't support IPv4-mapped results.
+#[cfg(any(
+    windows,
+    target_vendor = "apple",
+    target_os = "linux",
+    target_os = "l4re",
+    target_os = "android",
+    target_os = "emscripten",
+    target_os = "fuchsia",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "aix",
+    target_os = "cygwin",
+    target_os = "hurd",
+    target_os = "redox",
+))]
+#[allow(clippy::unnecessary_cast)]
+impl AddrInfoFlags {
+    /// Return IPv4-mapped IPv6 addresses if no IPv6 addresses are found.
+    pub const V4MAPPED: Self = AddrInfoFlags(c::AI_V4MAPPED as c_int);
+    /// With `V4MAPPED`, return both IPv6 and IPv4-mapped addresses.
+    pub const ALL: Self = AddrInfoFlags(c::AI_ALL as c_int);
+}
+
+#[cfg(any(
+    windows,
+    target_vendor = "apple",
+    target_env = "newlib",
+    target_os = "linux",
+    target_os = "l4re",
+    target_os = "android",
+    target_os = "emscripten",
+    target_os = "fuchsia",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    target_os = "aix",
+    target_os = "cygwin",
+    target_os = "hurd",
+    target_os = "redox",
+))]
+#[allow(clippy::unnecessary_cast)]
+impl AddrInfoFlags {
+    /// Only return address families configured on the local system.
+    pub const ADDRCONFIG: Self = AddrInfoFlags(c::AI_ADDRCONFIG as c_int);
+}
+
+impl_flags!(AddrInfoFlags);
+
+/// Flags for `getnameinfo`
+///
+/// Cross platform bitflags for the `flags` argument of `getnameinfo`,
+/// which can be combined with `|`. Values are converted to the platform's
+/// `NI_*` constants.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct NameInfoFlags(c_int);
+
+#[allow(clippy::unnecessary_cast)]
+impl NameInfoFlags {
+    /// Only return the hostname part of the fully qualified domain name for
+    /// local hosts.
+    pub const NOFQDN: Self = NameInfoFlags(c::NI_NOFQDN as c_int);
+    /// Return the numeric form of the host address.
+    pub const NUMERICHOST: Self = NameInfoFlags(c::NI_NUMERICHOST as c_int);
+    /// Return an error if the hostname can't be determined.
+    pub const NAMEREQD: Self = NameInfoFlags(c::NI_NAMEREQD as c_int);
+    /// Return the numeric form of the service port.
+    pub const NUMERICSERV: Self = NameInfoFlags(c::NI_NUMERICSERV as c_int);
+    /// The service is datagram (UDP) based, rather than stream (TCP) based.
+    pub const DGRAM: Self = NameInfoFlags(c::NI_DGRAM as c_int);
+}
+
+impl_flags!(NameInfoFlags);
+
+#[test]
+fn test_flags() {
+    // CANONNAME is the only flag defined on every platform.
+    let other = AddrInfoFlags::from_bits_retain(0x4000_0000);
+    let mut flags = AddrInfoFlags::CANONNAME | other;
+    assert!(flags.contains(AddrInfoFlags::CANONNAME));
+    assert!(!AddrInfoFlags::CANONNAME.contains(other));
+    flags.remove(AddrInfoFlags::CANONNAME);
+    assert_eq!(flags, other);
+    assert_eq!(c_int::from(flags), other.bits());
+    assert_eq!(AddrInfoFlags::from(flags.bits()), flags);
+    assert!(AddrInfoFlags::default().is_empty());
+
+    #[allow(clippy::unnecessary_cast)]
+    let raw = (c::NI_NUMERICSERV | c::NI_NAMEREQD) as c_int;
+    assert_eq!(
+        NameInfoFlags::NUMERICSERV | NameInfoFlags::NAMEREQD,
+        NameInfoFlags::from_bits_retain(raw)
+    );
+}